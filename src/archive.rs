@@ -0,0 +1,417 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use sevenz_rust::{Password, SevenZReader};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+/// Formats of archives that can be extracted
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+	Zip,
+	TarGz,
+	TarXz,
+	#[serde(rename = "7z")]
+	SevenZip,
+}
+
+impl ArchiveFormat {
+	/// Get the file extension that archives in this format have
+	pub fn get_extension(&self) -> &'static str {
+		match self {
+			Self::Zip => "zip",
+			Self::TarGz => "tar.gz",
+			Self::TarXz => "tar.xz",
+			Self::SevenZip => "7z",
+		}
+	}
+}
+
+/// Extract an archive into a directory, replacing what was in it. The files are extracted next to
+/// the directory first and swapped in once they are all there, so a failed extract leaves the old
+/// files untouched. If `strip_top_level` is set and everything in the archive is inside of a single
+/// directory, the contents of that directory are used instead
+pub fn extract(
+	path: &Path,
+	format: ArchiveFormat,
+	dir: &Path,
+	strip_top_level: bool,
+) -> anyhow::Result<()> {
+	let staging = get_sibling(dir, "new")?;
+	remove_dir_if_exists(&staging).context("Failed to remove old partial extract")?;
+	std::fs::create_dir_all(&staging).context("Failed to create extract directory")?;
+
+	let result = extract_to(path, format, &staging);
+	if let Err(e) = result {
+		let _ = std::fs::remove_dir_all(&staging);
+		return Err(e);
+	}
+
+	let contents = if strip_top_level {
+		find_top_level(&staging)?.unwrap_or_else(|| staging.clone())
+	} else {
+		staging.clone()
+	};
+	let result = swap_dir(&contents, dir);
+	let _ = remove_dir_if_exists(&staging);
+	result
+}
+
+fn extract_to(path: &Path, format: ArchiveFormat, dir: &Path) -> anyhow::Result<()> {
+	let file = File::open(path).context("Failed to open archive")?;
+	let reader = BufReader::new(file);
+	match format {
+		ArchiveFormat::Zip => extract_zip(reader, dir),
+		ArchiveFormat::TarGz => extract_tar(GzDecoder::new(reader), dir),
+		ArchiveFormat::TarXz => extract_tar(XzDecoder::new(reader), dir),
+		ArchiveFormat::SevenZip => extract_7z(path, dir),
+	}
+}
+
+fn extract_zip(reader: BufReader<File>, dir: &Path) -> anyhow::Result<()> {
+	let mut archive = ZipArchive::new(reader).context("Failed to read zip archive")?;
+	for i in 0..archive.len() {
+		let mut entry = archive
+			.by_index(i)
+			.context("Failed to read entry in zip archive")?;
+		let path = get_entry_path(dir, entry.name())?;
+		if entry.is_dir() {
+			create_dir(&path)?;
+		} else {
+			write_file(&path, &mut entry)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn extract_tar(reader: impl Read, dir: &Path) -> anyhow::Result<()> {
+	let mut archive = tar::Archive::new(reader);
+	let entries = archive.entries().context("Failed to read tar archive")?;
+	for entry in entries {
+		let mut entry = entry.context("Failed to read entry in tar archive")?;
+		let name = entry.path().context("Invalid path in tar archive")?;
+		let path = get_entry_path(dir, &name.to_string_lossy())?;
+		let entry_type = entry.header().entry_type();
+		if entry_type.is_dir() {
+			create_dir(&path)?;
+		} else if entry_type.is_file() {
+			write_file(&path, &mut entry)?;
+		}
+		// Links could point outside of the directory, and nothing that is installed needs them
+	}
+
+	Ok(())
+}
+
+fn extract_7z(path: &Path, dir: &Path) -> anyhow::Result<()> {
+	let mut archive =
+		SevenZReader::open(path, Password::empty()).context("Failed to read 7z archive")?;
+	// Errors in the callback have to be 7z errors, so the real error is kept here
+	let mut error = None;
+	let result = archive.for_each_entries(|entry, reader| {
+		let result = get_entry_path(dir, entry.name()).and_then(|path| {
+			if entry.is_directory() {
+				create_dir(&path)
+			} else {
+				write_file(&path, reader)
+			}
+		});
+		match result {
+			Ok(()) => Ok(true),
+			Err(e) => {
+				error = Some(e);
+				Ok(false)
+			}
+		}
+	});
+
+	if let Some(e) = error {
+		return Err(e);
+	}
+	result.context("Failed to read entry in 7z archive")
+}
+
+/// Get the path that an entry in an archive is extracted to, failing if it would be outside of the directory
+fn get_entry_path(dir: &Path, name: &str) -> anyhow::Result<PathBuf> {
+	let mut out = dir.to_path_buf();
+	// Archives made on Windows can use either separator
+	for part in name.split(['/', '\\']) {
+		match part {
+			// Absolute paths start with an empty part, so they end up inside of the directory
+			"" | "." => {}
+			".." => bail!("The archive contains a file outside of its directory: {name}"),
+			// Colons are used for drive letters and alternate data streams
+			_ if part.contains([':', '\0']) => {
+				bail!("The archive contains a file with an invalid name: {name}")
+			}
+			_ => out.push(part),
+		}
+	}
+	if out == dir {
+		bail!("The archive contains an entry without a name");
+	}
+
+	Ok(out)
+}
+
+fn create_dir(path: &Path) -> anyhow::Result<()> {
+	std::fs::create_dir_all(path)
+		.with_context(|| format!("Failed to create directory {}", path.display()))
+}
+
+fn write_file(path: &Path, reader: &mut (impl Read + ?Sized)) -> anyhow::Result<()> {
+	if let Some(parent) = path.parent() {
+		create_dir(parent)?;
+	}
+	let file =
+		File::create(path).with_context(|| format!("Failed to create file {}", path.display()))?;
+	let mut file = BufWriter::new(file);
+	std::io::copy(reader, &mut file)
+		.and_then(|_| file.flush())
+		.with_context(|| format!("Failed to extract file {}", path.display()))
+}
+
+/// Find the directory that all of the extracted files are in, if there is only one
+fn find_top_level(dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+	let mut entries = std::fs::read_dir(dir).context("Failed to read extracted files")?;
+	let (Some(first), None) = (entries.next(), entries.next()) else {
+		return Ok(None);
+	};
+	let first = first.context("Failed to read extracted files")?;
+	if first.file_type()?.is_dir() {
+		Ok(Some(first.path()))
+	} else {
+		Ok(None)
+	}
+}
+
+/// Replace a directory with another one on the same drive. The old directory is moved aside first
+/// so that it can be put back if the new one can't be moved in
+fn swap_dir(new: &Path, dir: &Path) -> anyhow::Result<()> {
+	let old = get_sibling(dir, "old")?;
+	remove_dir_if_exists(&old).context("Failed to remove old files")?;
+	if dir.exists() {
+		// This fails if a program in the directory is running
+		std::fs::rename(dir, &old).with_context(|| {
+			format!(
+				"Failed to replace {}. Close any programs that are using it",
+				dir.display()
+			)
+		})?;
+	}
+
+	if let Err(e) = std::fs::rename(new, dir) {
+		if old.exists() {
+			let _ = std::fs::rename(&old, dir);
+		}
+		return Err(e).context("Failed to move extracted files into place");
+	}
+
+	// The new files are in place, so old files that can't be removed yet are cleaned up next time
+	let _ = remove_dir_if_exists(&old);
+	Ok(())
+}
+
+/// Get a path next to a directory with a suffix on its name
+fn get_sibling(dir: &Path, suffix: &str) -> anyhow::Result<PathBuf> {
+	let name = dir
+		.file_name()
+		.ok_or(anyhow!("Invalid extract directory {}", dir.display()))?;
+	Ok(dir.with_file_name(format!("{}.{suffix}", name.to_string_lossy())))
+}
+
+fn remove_dir_if_exists(dir: &Path) -> std::io::Result<()> {
+	if dir.exists() {
+		std::fs::remove_dir_all(dir)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use flate2::write::GzEncoder;
+	use flate2::Compression;
+	use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+	use xz2::write::XzEncoder;
+	use zip::write::FileOptions;
+	use zip::ZipWriter;
+
+	use super::*;
+
+	/// Files in the test archives, which are all in a top level directory
+	const FILES: &[(&str, &[u8])] = &[
+		("App-1.0/app.exe", b"program"),
+		("App-1.0/lib/data.txt", b"data"),
+	];
+
+	fn write_zip(file: File, files: &[(&str, &[u8])]) {
+		let mut zip = ZipWriter::new(file);
+		zip.add_directory("App-1.0/", FileOptions::default())
+			.unwrap();
+		for (name, contents) in files {
+			zip.start_file(*name, FileOptions::default()).unwrap();
+			zip.write_all(contents).unwrap();
+		}
+		zip.finish().unwrap();
+	}
+
+	fn write_7z(file: File, files: &[(&str, &[u8])]) {
+		let mut writer = SevenZWriter::new(file).unwrap();
+		for (name, contents) in files {
+			let mut entry = SevenZArchiveEntry::new();
+			entry.name = name.to_string();
+			entry.has_stream = true;
+			writer.push_archive_entry(entry, Some(*contents)).unwrap();
+		}
+		writer.finish().unwrap();
+	}
+
+	/// Write a tar archive. The names are written directly into the headers, since the tar
+	/// builder refuses names that go outside of the archive
+	fn write_tar(writer: impl Write, files: &[(&str, &[u8])]) {
+		let mut tar = tar::Builder::new(writer);
+		for (name, contents) in files {
+			let mut header = tar::Header::new_old();
+			header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+			header.set_size(contents.len() as u64);
+			header.set_mode(0o644);
+			header.set_cksum();
+			tar.append(&header, *contents).unwrap();
+		}
+		tar.into_inner().unwrap().flush().unwrap();
+	}
+
+	fn write_archive(path: &Path, format: ArchiveFormat, files: &[(&str, &[u8])]) {
+		let file = File::create(path).unwrap();
+		match format {
+			ArchiveFormat::Zip => write_zip(file, files),
+			ArchiveFormat::TarGz => {
+				let mut encoder = GzEncoder::new(file, Compression::default());
+				write_tar(&mut encoder, files);
+				encoder.finish().unwrap();
+			}
+			ArchiveFormat::TarXz => {
+				let mut encoder = XzEncoder::new(file, 6);
+				write_tar(&mut encoder, files);
+				encoder.finish().unwrap();
+			}
+			ArchiveFormat::SevenZip => write_7z(file, files),
+		}
+	}
+
+	/// Extract an archive of the test files in a format into a directory that has old files in it
+	fn check_extract(format: ArchiveFormat) {
+		let temp = tempfile::tempdir().unwrap();
+		let archive = temp
+			.path()
+			.join(format!("archive.{}", format.get_extension()));
+		write_archive(&archive, format, FILES);
+		let dir = temp.path().join("extracted");
+		std::fs::create_dir(&dir).unwrap();
+		std::fs::write(dir.join("old.txt"), "old").unwrap();
+
+		extract(&archive, format, &dir, true).unwrap();
+		assert_eq!(std::fs::read(dir.join("app.exe")).unwrap(), b"program");
+		assert_eq!(std::fs::read(dir.join("lib/data.txt")).unwrap(), b"data");
+		assert!(!dir.join("old.txt").exists());
+		assert!(!temp.path().join("extracted.new").exists());
+		assert!(!temp.path().join("extracted.old").exists());
+
+		extract(&archive, format, &dir, false).unwrap();
+		assert_eq!(
+			std::fs::read(dir.join("App-1.0/app.exe")).unwrap(),
+			b"program"
+		);
+	}
+
+	/// Extract an archive with a file outside of its directory and check that nothing changed
+	fn check_traversal(format: ArchiveFormat) {
+		let temp = tempfile::tempdir().unwrap();
+		let archive = temp
+			.path()
+			.join(format!("archive.{}", format.get_extension()));
+		let files: &[(&str, &[u8])] = &[("App-1.0/app.exe", b"program"), ("../evil.txt", b"evil")];
+		write_archive(&archive, format, files);
+		let dir = temp.path().join("extracted");
+		std::fs::create_dir(&dir).unwrap();
+		std::fs::write(dir.join("old.txt"), "old").unwrap();
+
+		let error = extract(&archive, format, &dir, false).unwrap_err();
+		assert!(format!("{error:#}").contains("outside of its directory"));
+		assert!(!temp.path().join("evil.txt").exists());
+		assert_eq!(std::fs::read(dir.join("old.txt")).unwrap(), b"old");
+		assert!(!temp.path().join("extracted.new").exists());
+	}
+
+	#[test]
+	fn test_extract_zip() {
+		check_extract(ArchiveFormat::Zip);
+	}
+
+	#[test]
+	fn test_extract_tar_gz() {
+		check_extract(ArchiveFormat::TarGz);
+	}
+
+	#[test]
+	fn test_extract_tar_xz() {
+		check_extract(ArchiveFormat::TarXz);
+	}
+
+	#[test]
+	fn test_extract_7z() {
+		check_extract(ArchiveFormat::SevenZip);
+	}
+
+	#[test]
+	fn test_zip_traversal() {
+		check_traversal(ArchiveFormat::Zip);
+	}
+
+	#[test]
+	fn test_tar_traversal() {
+		check_traversal(ArchiveFormat::TarGz);
+		check_traversal(ArchiveFormat::TarXz);
+	}
+
+	#[test]
+	fn test_7z_traversal() {
+		check_traversal(ArchiveFormat::SevenZip);
+	}
+
+	#[test]
+	fn test_entry_paths() {
+		let dir = Path::new("out");
+		assert_eq!(
+			get_entry_path(dir, "a\\b/c.txt").unwrap(),
+			dir.join("a").join("b").join("c.txt")
+		);
+		assert_eq!(
+			get_entry_path(dir, "/abs.txt").unwrap(),
+			dir.join("abs.txt")
+		);
+		assert!(get_entry_path(dir, "a/../../b").is_err());
+		assert!(get_entry_path(dir, "C:/b").is_err());
+		assert!(get_entry_path(dir, "./").is_err());
+	}
+
+	#[test]
+	fn test_swap_dir_rollback() {
+		let temp = tempfile::tempdir().unwrap();
+		let dir = temp.path().join("extracted");
+		std::fs::create_dir(&dir).unwrap();
+		std::fs::write(dir.join("old.txt"), "old").unwrap();
+
+		// The new directory does not exist, so moving it into place fails
+		let result = swap_dir(&temp.path().join("missing"), &dir);
+		assert!(result.is_err());
+		assert_eq!(std::fs::read(dir.join("old.txt")).unwrap(), b"old");
+		assert!(!temp.path().join("extracted.old").exists());
+	}
+}
@@ -21,3 +21,22 @@ pub static REV_ICON: &[u8] = include_bytes!("rev.bmp");
 /// NI icon
 pub static NI_ICON: &[u8] = include_bytes!("ni.bmp");
 
+/// Default package manifest
+pub static DEFAULT_MANIFEST: &str = include_str!("packages.json");
+
+/// Get a built-in icon by its name in the package manifest
+pub fn get_icon(name: &str) -> Option<&'static [u8]> {
+	match name {
+		"worbots" => Some(WORBOTS_ICON),
+		"limelight" => Some(LIMELIGHT_ICON),
+		"advantagescope" => Some(ADVANTAGESCOPE_ICON),
+		"ctre" => Some(CTRE_ICON),
+		"wpilib" => Some(WPILIB_ICON),
+		"rev" => Some(REV_ICON),
+		"ni" => Some(NI_ICON),
+		_ => None,
+	}
+}
+
+/// Public ed25519 key that team manifests must be signed with, in hex. Lines starting with # are comments
+pub static TEAM_MANIFEST_KEY: &str = include_str!("team_manifest.pub");
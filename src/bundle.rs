@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::data::Data;
+use crate::install::{download_installer, InstallFrom};
+use crate::lockfile::{LockedPackage, Lockfile};
+use crate::manifest::InstallSpec;
+use crate::output::OutputTrait;
+use crate::package::Package;
+
+/// Name of the manifest in a bundle directory. It is a lockfile for the files in the bundle
+pub const BUNDLE_MANIFEST: &str = "bundle.json";
+
+/// A directory with the installers for a set of packages, used to install them without a network
+pub struct Bundle {
+	dir: PathBuf,
+	pub lockfile: Lockfile,
+}
+
+impl Bundle {
+	/// Load a bundle from its directory
+	pub fn load(dir: &Path) -> anyhow::Result<Self> {
+		let lockfile =
+			Lockfile::load(&dir.join(BUNDLE_MANIFEST)).context("Failed to load bundle manifest")?;
+		Ok(Self {
+			dir: dir.to_owned(),
+			lockfile,
+		})
+	}
+
+	/// Get the packages that are in the bundle
+	pub fn get_packages(&self) -> anyhow::Result<Vec<Package>> {
+		self.lockfile
+			.packages
+			.keys()
+			.map(|x| x.parse().context("Bundle contains an unknown package"))
+			.collect()
+	}
+
+	/// Get the bundled file to install a package from
+	pub fn get(&self, package: Package) -> Option<InstallFrom<'_>> {
+		let locked = self.lockfile.get(package)?;
+		let spec = package.get_install_spec()?;
+		let path = get_bundle_file(&self.dir, package, spec);
+		Some(InstallFrom::File(path, locked))
+	}
+
+	/// Download the latest installers for packages into a bundle directory and write its manifest
+	pub async fn export(
+		packages: &[Package],
+		dir: &Path,
+		data: &mut Data<'_>,
+	) -> anyhow::Result<Self> {
+		let mut lockfile = Lockfile::new(data.season);
+		for package in packages {
+			let Some(spec) = package.get_install_spec() else {
+				continue;
+			};
+			let Some(source) = package.resolve_source(data, data.season).await? else {
+				continue;
+			};
+
+			data.out.progress(format!("Bundling package {package}"));
+			let cached = package
+				.with_network(download_installer(data, &source, &spec.get_file_name()))
+				.await?;
+			let path = get_bundle_file(dir, *package, spec);
+			std::fs::create_dir_all(path.parent().unwrap_or(dir))
+				.context("Failed to create bundle directory")?;
+			std::fs::copy(&cached.path, &path).context("Failed to copy installer into bundle")?;
+
+			lockfile.packages.insert(
+				package.id().to_string(),
+				LockedPackage {
+					tag: source.version,
+					asset: source.asset,
+					url: source.url,
+					sha256: cached.sha256,
+				},
+			);
+		}
+
+		lockfile.save(&dir.join(BUNDLE_MANIFEST))?;
+
+		Ok(Self {
+			dir: dir.to_owned(),
+			lockfile,
+		})
+	}
+}
+
+/// Get the path of the installer for a package in a bundle
+fn get_bundle_file(dir: &Path, package: Package, spec: &InstallSpec) -> PathBuf {
+	dir.join(package.id()).join(spec.get_file_name())
+}
@@ -0,0 +1,215 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::data::Data;
+use crate::manifest::ResolvedSource;
+
+/// Name of the download cache directory in the data directory
+pub const CACHE_DIR: &str = "cache";
+
+/// Content-addressed cache of downloaded installers. Each entry is keyed by the URL and checksum
+/// of its file, and is stored in its own directory so that the file can keep a normal name
+pub struct Cache {
+	dir: PathBuf,
+}
+
+/// A file in the download cache
+#[derive(Serialize, Deserialize)]
+pub struct CacheEntry {
+	pub key: String,
+	pub url: String,
+	/// File name of the asset that was downloaded
+	pub asset: String,
+	/// Name of the file in the cache entry
+	pub file_name: String,
+	/// SHA-256 checksum of the file in hex, if it was verified
+	pub sha256: Option<String>,
+	/// Size of the file in bytes
+	pub size: u64,
+	pub added_at: DateTime<Utc>,
+	pub last_used: DateTime<Utc>,
+}
+
+impl Cache {
+	/// Open the download cache in the data directory
+	pub fn new(data: &Data) -> anyhow::Result<Self> {
+		let dir = data.get_data_directory()?.join(CACHE_DIR);
+		std::fs::create_dir_all(&dir).context("Failed to create cache directory")?;
+		Ok(Self { dir })
+	}
+
+	/// Get the cache key for a URL and checksum
+	pub fn get_key(url: &str, sha256: Option<&str>) -> String {
+		let mut hasher = Sha256::new();
+		hasher.update(url.as_bytes());
+		hasher.update(b"\n");
+		hasher.update(sha256.unwrap_or_default().to_lowercase().as_bytes());
+		format!("{:x}", hasher.finalize())
+	}
+
+	/// Get the cached file for a source if there is one, marking it as used. Sources without a
+	/// checksum are never served from the cache, since the file at their URL can change without
+	/// the URL changing, like the latest release of a program
+	pub fn get(&self, source: &ResolvedSource) -> anyhow::Result<Option<PathBuf>> {
+		let Some(sha256) = &source.sha256 else {
+			return Ok(None);
+		};
+		let key = Self::get_key(&source.url, Some(sha256));
+		let Some(mut entry) = self.load_entry(&key) else {
+			return Ok(None);
+		};
+		let path = self.get_file_path(&entry);
+		if !path.exists() {
+			return Ok(None);
+		}
+
+		entry.last_used = Utc::now();
+		self.save_entry(&entry)?;
+		Ok(Some(path))
+	}
+
+	/// Get the path of the file for an entry
+	pub fn get_file_path(&self, entry: &CacheEntry) -> PathBuf {
+		self.dir.join(&entry.key).join(&entry.file_name)
+	}
+
+	/// Get the path to download the file for a source to before it is added to the cache
+	pub fn get_download_path(
+		&self,
+		source: &ResolvedSource,
+		file_name: &str,
+	) -> anyhow::Result<PathBuf> {
+		let key = Self::get_key(&source.url, source.sha256.as_deref());
+		let dir = self.dir.join(key);
+		std::fs::create_dir_all(&dir).context("Failed to create cache entry directory")?;
+		Ok(dir.join(file_name))
+	}
+
+	/// Add a file that was downloaded to its download path to the cache with its checksum, returning
+	/// the path of the file in the cache. Files are only used from the cache once they are added,
+	/// so partial or corrupt downloads are never reused. Files from sources without a checksum are
+	/// moved to the entry for the checksum they turned out to have, so that a lockfile with that
+	/// checksum can use them
+	pub fn add(
+		&self,
+		source: &ResolvedSource,
+		path: &Path,
+		sha256: &str,
+	) -> anyhow::Result<PathBuf> {
+		let file_name = path.file_name().unwrap_or_default().to_owned();
+		let key = Self::get_key(&source.url, Some(sha256));
+		let dir = self.dir.join(&key);
+		let cached_path = dir.join(&file_name);
+		if cached_path != path {
+			if dir.exists() {
+				std::fs::remove_dir_all(&dir).context("Failed to replace cached file")?;
+			}
+			std::fs::create_dir_all(&dir).context("Failed to create cache entry directory")?;
+			std::fs::rename(path, &cached_path).context("Failed to move file into the cache")?;
+			if let Some(download_dir) = path.parent() {
+				let _ = std::fs::remove_dir_all(download_dir);
+			}
+		}
+
+		let size = std::fs::metadata(&cached_path)
+			.context("Failed to read cached file")?
+			.len();
+		let now = Utc::now();
+		let entry = CacheEntry {
+			key,
+			url: source.url.clone(),
+			asset: source.asset.clone(),
+			file_name: file_name.to_string_lossy().into_owned(),
+			sha256: Some(sha256.to_string()),
+			size,
+			added_at: now,
+			last_used: now,
+		};
+		self.save_entry(&entry)?;
+
+		Ok(cached_path)
+	}
+
+	/// List the entries in the cache, most recently used first
+	pub fn list(&self) -> anyhow::Result<Vec<CacheEntry>> {
+		let mut out = Vec::new();
+		for file in std::fs::read_dir(&self.dir).context("Failed to read cache directory")? {
+			let path = file?.path();
+			if path.extension().is_some_and(|x| x == "json") {
+				let key = path.file_stem().unwrap_or_default().to_string_lossy();
+				out.extend(self.load_entry(&key));
+			}
+		}
+		out.sort_by_key(|x| Reverse(x.last_used));
+
+		Ok(out)
+	}
+
+	/// Get the size of everything in the cache in bytes, including partial downloads
+	pub fn size(&self) -> anyhow::Result<u64> {
+		get_dir_size(&self.dir).context("Failed to read cache directory")
+	}
+
+	/// Remove an entry from the cache
+	pub fn remove(&self, entry: &CacheEntry) -> anyhow::Result<()> {
+		let dir = self.dir.join(&entry.key);
+		if dir.exists() {
+			std::fs::remove_dir_all(dir).context("Failed to remove cached file")?;
+		}
+		std::fs::remove_file(self.get_entry_path(&entry.key))
+			.context("Failed to remove cache entry")?;
+		Ok(())
+	}
+
+	/// Remove all but the most recently used entries, returning the removed entries
+	pub fn prune(&self, keep: usize) -> anyhow::Result<Vec<CacheEntry>> {
+		let removed: Vec<_> = self.list()?.into_iter().skip(keep).collect();
+		for entry in &removed {
+			self.remove(entry)?;
+		}
+		Ok(removed)
+	}
+
+	/// Remove everything from the cache
+	pub fn clear(&self) -> anyhow::Result<()> {
+		std::fs::remove_dir_all(&self.dir).context("Failed to remove cache directory")?;
+		std::fs::create_dir_all(&self.dir).context("Failed to create cache directory")?;
+		Ok(())
+	}
+
+	fn get_entry_path(&self, key: &str) -> PathBuf {
+		self.dir.join(format!("{key}.json"))
+	}
+
+	fn load_entry(&self, key: &str) -> Option<CacheEntry> {
+		let contents = std::fs::read_to_string(self.get_entry_path(key)).ok()?;
+		serde_json::from_str(&contents).ok()
+	}
+
+	fn save_entry(&self, entry: &CacheEntry) -> anyhow::Result<()> {
+		let contents = serde_json::to_string_pretty(entry)?;
+		std::fs::write(self.get_entry_path(&entry.key), contents)
+			.context("Failed to write cache entry")?;
+		Ok(())
+	}
+}
+
+/// Get the total size of the files in a directory
+fn get_dir_size(dir: &Path) -> std::io::Result<u64> {
+	let mut out = 0;
+	for file in std::fs::read_dir(dir)? {
+		let file = file?;
+		let meta = file.metadata()?;
+		if meta.is_dir() {
+			out += get_dir_size(&file.path())?;
+		} else {
+			out += meta.len();
+		}
+	}
+	Ok(out)
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::net::{NetworkPolicy, ProxyConfig};
+use crate::season::Season;
+
+/// Name of the config file in the data directory
+pub const CONFIG_FILE: &str = "config.json";
+
+/// User and team configuration for the tool
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+	/// URL of the signed team manifest. The signature is fetched from the same URL with `.sig` appended
+	pub manifest_url: Option<String>,
+	/// Install profiles, mapping a profile name to the IDs of the packages in it
+	pub profiles: BTreeMap<String, Vec<String>>,
+	/// The season to install and launch packages for, instead of the current one
+	pub season: Option<Season>,
+	/// URL of another computer running the serve command to download files from before
+	/// trying their upstream URL
+	pub mirror_url: Option<String>,
+	/// Github token to use for API requests, which raises the rate limit. The GITHUB_TOKEN
+	/// environment variable is used if this is not set
+	pub github_token: Option<String>,
+	/// Timeouts and retries for network requests
+	pub network: NetworkPolicy,
+	/// Proxies to send requests through
+	pub proxy: ProxyConfig,
+	/// PEM files with extra root certificates to trust, such as the certificate of a school proxy
+	pub ca_bundles: Vec<PathBuf>,
+}
+
+impl Config {
+	/// Load the config from the data directory, or use the default config if it does not exist
+	pub fn load(data_dir: &Path) -> anyhow::Result<Self> {
+		let path = data_dir.join(CONFIG_FILE);
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+
+		let contents = std::fs::read_to_string(&path).context("Failed to read config file")?;
+		let out = serde_json::from_str(&contents).context("Failed to parse config file")?;
+		Ok(out)
+	}
+}
@@ -1,15 +1,21 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::{output::Output, package::Package};
+use crate::{config::Config, net::create_client, output::Output, package::Package, season::Season};
 
 /// Container for project directories, data, and other shared state
 pub struct Data<'o> {
 	pub dirs: ProjectDirs,
+	pub config: Config,
+	/// The season that packages are installed and launched for
+	pub season: Season,
+	/// Whether installers should run without asking for input
+	pub silent: bool,
 	pub client: Client,
 	pub out: &'o mut Output,
 }
@@ -19,9 +25,19 @@ impl<'o> Data<'o> {
 		let dirs = directories::ProjectDirs::from("worbots_setup", "4145", "worbots_setup")
 			.ok_or(anyhow!("Failed to create project directories"))?;
 
+		std::fs::create_dir_all(dirs.data_dir())?;
+		let config = Config::load(dirs.data_dir()).context("Failed to load config")?;
+
+		let season = config.season.unwrap_or_else(Season::current);
+
+		let client = create_client(&config)?;
+
 		let out = Data {
 			dirs,
-			client: Client::new(),
+			config,
+			season,
+			silent: false,
+			client,
 			out,
 		};
 
@@ -35,8 +51,95 @@ impl<'o> Data<'o> {
 	}
 }
 
+/// Name of the persistent data file in the data directory
+const PERSISTENT_DATA_FILE: &str = "state.json";
+
 /// Persistent data for the tool
 #[derive(Serialize, Deserialize)]
 pub struct PersistentData {
-	pub installed_packages: Vec<Package>,
+	/// Version of the data format, used to migrate old files
+	pub version: u32,
+	pub installed_packages: Vec<InstalledPackage>,
+}
+
+impl PersistentData {
+	/// The current version of the data format
+	pub const VERSION: u32 = 1;
+
+	/// Load the persistent data, or create empty data if it has not been saved yet
+	pub fn load(data: &Data) -> anyhow::Result<Self> {
+		let path = data.get_data_directory()?.join(PERSISTENT_DATA_FILE);
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+
+		let contents = std::fs::read_to_string(path).context("Failed to read persistent data")?;
+		let out: Self =
+			serde_json::from_str(&contents).context("Failed to parse persistent data")?;
+		if out.version > Self::VERSION {
+			bail!(
+				"Persistent data has version {}, but this tool only supports up to version {}. Update the tool",
+				out.version,
+				Self::VERSION
+			);
+		}
+
+		Ok(out)
+	}
+
+	/// Write the persistent data to disk
+	pub fn save(&self, data: &Data) -> anyhow::Result<()> {
+		let path = data.get_data_directory()?.join(PERSISTENT_DATA_FILE);
+		let contents = serde_json::to_string_pretty(self)?;
+		std::fs::write(path, contents).context("Failed to write persistent data")?;
+		Ok(())
+	}
+
+	/// Record that a package was installed, replacing any previous record for it in the same season
+	pub fn record_install(&mut self, installed: InstalledPackage) {
+		self.installed_packages
+			.retain(|x| x.package != installed.package || x.season != installed.season);
+		self.installed_packages.push(installed);
+	}
+
+	/// Remove the install record for a package in a season
+	pub fn remove_install(&mut self, package: Package, season: Season) {
+		let season = package.get_season(season);
+		self.installed_packages
+			.retain(|x| x.package != package.id() || x.season != season);
+	}
+
+	/// Get the install record for a package in a season
+	pub fn get_installed(&self, package: Package, season: Season) -> Option<&InstalledPackage> {
+		let season = package.get_season(season);
+		self.installed_packages
+			.iter()
+			.find(|x| x.package == package.id() && x.season == season)
+	}
+}
+
+impl Default for PersistentData {
+	fn default() -> Self {
+		Self {
+			version: Self::VERSION,
+			installed_packages: Vec::new(),
+		}
+	}
+}
+
+/// Record of a package that was installed by the tool
+#[derive(Serialize, Deserialize)]
+pub struct InstalledPackage {
+	/// ID of the package. This is not a Package so that packages removed from the manifest can still be loaded
+	pub package: String,
+	/// The season that the package was installed for, if each season has its own install
+	#[serde(default)]
+	pub season: Option<Season>,
+	/// Release tag of the installed version, if it is known
+	pub version: Option<String>,
+	/// URL of the file that was installed
+	pub asset_url: String,
+	pub installed_at: DateTime<Utc>,
+	/// Where the package was installed to, if it is known
+	pub install_path: Option<PathBuf>,
 }
@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use anyhow::bail;
+
+/// Order a set of nodes and all of their transitive dependencies so that every node comes after
+/// its dependencies. Nodes that do not depend on each other keep the order they were given in.
+/// Fails if there is a dependency cycle
+pub fn topological_sort<T, F, I>(roots: &[T], mut get_deps: F) -> anyhow::Result<Vec<T>>
+where
+	T: Copy + Eq + Hash + Display,
+	F: FnMut(T) -> I,
+	I: IntoIterator<Item = T>,
+{
+	let mut out = Vec::new();
+	let mut done = HashSet::new();
+	let mut stack = Vec::new();
+	for root in roots {
+		visit(*root, &mut get_deps, &mut done, &mut stack, &mut out)?;
+	}
+
+	Ok(out)
+}
+
+fn visit<T, F, I>(
+	node: T,
+	get_deps: &mut F,
+	done: &mut HashSet<T>,
+	stack: &mut Vec<T>,
+	out: &mut Vec<T>,
+) -> anyhow::Result<()>
+where
+	T: Copy + Eq + Hash + Display,
+	F: FnMut(T) -> I,
+	I: IntoIterator<Item = T>,
+{
+	if done.contains(&node) {
+		return Ok(());
+	}
+	if let Some(pos) = stack.iter().position(|x| *x == node) {
+		let cycle: Vec<_> = stack[pos..]
+			.iter()
+			.chain(std::iter::once(&node))
+			.map(ToString::to_string)
+			.collect();
+		bail!("Dependency cycle detected: {}", cycle.join(" -> "));
+	}
+
+	stack.push(node);
+	for dep in get_deps(node) {
+		visit(dep, get_deps, done, stack, out)?;
+	}
+	stack.pop();
+
+	done.insert(node);
+	out.push(node);
+
+	Ok(())
+}
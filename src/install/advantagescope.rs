@@ -1,48 +1,10 @@
-use std::path::PathBuf;
-use std::process::Command;
-
-use anyhow::{anyhow, Context};
+use anyhow::Context;
 
 use crate::assets;
-use crate::data::Data;
-use crate::output::OutputTrait;
-use crate::utils::{download_file, download_github_release, get_data_dir, get_local_program};
-
-pub async fn install(data: &mut Data<'_>) -> anyhow::Result<()> {
-	let dir = get_path(data)?;
-
-	data.out.progress("Getting Github release");
-	let release = download_github_release(&data.client, "Mechanical-Advantage", "AdvantageScope")
-		.await
-		.context("Failed to get Github release")?;
-	let asset = release
-		.get_asset_pattern("win-x64")
-		.ok_or(anyhow!("No valid asset file found"))?;
-
-	// Download the installer
-	data.out.progress("Downloading installer");
-	let installer_path = dir.join("installer.exe");
-	download_file(&data.client, &asset.browser_download_url, &installer_path).await?;
+use crate::utils::get_data_dir;
 
-	// Run the installer
-	data.out.progress("Starting installer");
-	Command::new(installer_path).spawn()?.wait()?;
-
-	// Configure AdvantageScope
-	data.out.progress("Finished installer. Configuring");
-	configure().context("Failed to configure")?;
-
-	Ok(())
-}
-
-pub fn launch(_data: &mut Data<'_>) -> anyhow::Result<()> {
-	let exec = get_local_program("advantagescope", "AdvantageScope.exe")?;
-	Command::new(exec).spawn()?;
-
-	Ok(())
-}
-
-fn configure() -> anyhow::Result<()> {
+/// Write the team preferences and joystick configs to the AdvantageScope data directory
+pub fn configure() -> anyhow::Result<()> {
 	let as_dir =
 		get_data_dir("AdvantageScope").context("Failed to get AdvantageScope directory")?;
 
@@ -75,9 +37,3 @@ fn configure() -> anyhow::Result<()> {
 
 	Ok(())
 }
-
-fn get_path(data: &Data) -> anyhow::Result<PathBuf> {
-	let out = data.get_data_directory()?.join("advantagescope");
-	std::fs::create_dir_all(&out)?;
-	Ok(out)
-}
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+
+use crate::archive::{self, ArchiveFormat};
+use crate::data::Data;
+use crate::iso::DiscImage;
+use crate::output::OutputTrait;
+
+use super::InstallerCommand;
+
+/// Run the file as an installer program and wait for it to finish
+pub fn run(path: &Path, command: &InstallerCommand, data: &mut Data) -> anyhow::Result<()> {
+	data.out.progress("Starting installer");
+	command.run(path, data)
+}
+
+/// Run the file as an installer program with administrator permissions and wait for it to finish
+pub fn run_elevated(
+	path: &Path,
+	command: &InstallerCommand,
+	data: &mut Data,
+) -> anyhow::Result<()> {
+	data.out.progress("Starting installer");
+	command.show_instruction(data);
+	let mut child = command
+		.create_elevated(path)?
+		.spawn()
+		.context("Failed to start installer")?;
+	if !command.is_silent() {
+		data.out
+			.instruction("The installer has started. Follow the steps it gives you");
+	}
+
+	// The command prompt waits for the installer and exits with its exit code, so the install is
+	// only recorded once the installer has finished successfully
+	let status = child.wait().context("Failed to wait for installer")?;
+	command.check_status(status, data)
+}
+
+/// Extract the file as an archive into a directory
+pub fn extract_archive(
+	path: &Path,
+	format: ArchiveFormat,
+	dir: &Path,
+	strip_top_level: bool,
+	data: &mut Data,
+) -> anyhow::Result<()> {
+	data.out.progress("Extracting archive");
+	archive::extract(path, format, dir, strip_top_level).context("Failed to extract archive")
+}
+
+/// Extract the file as a disc image into a directory and run an installer program from it
+pub fn extract_iso(
+	image_path: &Path,
+	program: &str,
+	dir: &Path,
+	command: &InstallerCommand,
+	data: &mut Data,
+) -> anyhow::Result<()> {
+	data.out.progress("Extracting image");
+	// Files from an install that was stopped part way through can still be here
+	if dir.exists() {
+		std::fs::remove_dir_all(dir).context("Failed to remove old extracted image")?;
+	}
+	let mut image = DiscImage::open(image_path)?;
+	image
+		.extract(dir)
+		.with_context(|| format!("Failed to extract {} image", image.filesystem()))?;
+
+	let installer_path = dir.join(program);
+	let result = if installer_path.exists() {
+		data.out.progress("Starting installer");
+		command.run(&installer_path, data)
+	} else {
+		Err(anyhow!("The image does not contain {program}"))
+	};
+
+	// Remove the extracted files even if the installer failed
+	data.out.progress("Removing extracted image");
+	std::fs::remove_dir_all(dir).context("Failed to remove extracted image")?;
+
+	result
+}
@@ -1,55 +1,530 @@
+use std::fmt::Display;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::cache::Cache;
+use crate::data::Data;
+use crate::lockfile::LockedPackage;
+use crate::manifest::{
+	Configuration, InstallMethod, InstallSpec, InstallerExit, InstallerTechnology, LaunchTarget,
+	ResolvedSource, UninstallMethod,
+};
+use crate::output::OutputTrait;
+use crate::package::Package;
+use crate::paths::PathContext;
+use crate::utils::{download_file, file_sha256, run_elevated};
+
 pub mod advantagescope;
-pub mod cachecad;
-pub mod etcher;
-pub mod game_tools;
-pub mod github_desktop;
-pub mod grip;
-pub mod limelight_finder;
-pub mod pathplanner;
-pub mod phoenix;
-pub mod rev_client;
-pub mod wpilib;
+pub mod methods;
 
-pub mod misc {
-	use std::path::PathBuf;
+/// Where the file to install a package from comes from
+pub enum InstallFrom<'a> {
+	/// The latest release from the package source
+	Latest,
+	/// The file in a lockfile
+	Locked(&'a LockedPackage),
+	/// A file that is already on disk, such as in an offline bundle, with its lockfile entry
+	File(PathBuf, &'a LockedPackage),
+}
 
-	use crate::{data::Data, utils::run_elevated};
+/// An installer or uninstaller program that did not finish, with the kind of program that it is
+#[derive(Debug)]
+pub enum InstallerError {
+	/// The user cancelled the program
+	Cancelled(&'static str),
+	/// The program failed, with its exit code if it has one
+	Failed(&'static str, Option<i32>),
+}
 
-	pub fn launch_task_manager(_data: &mut Data<'_>) -> anyhow::Result<()> {
-		let exec = PathBuf::from("C:/WINDOWS/system32/Taskmgr.exe");
-		run_elevated(exec)?.spawn()?.wait()?;
-		Ok(())
+impl Display for InstallerError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Cancelled(program) => write!(f, "The {program} was cancelled"),
+			Self::Failed(program, Some(code)) => {
+				write!(f, "The {program} failed with exit code {code}")
+			}
+			Self::Failed(program, None) => {
+				write!(f, "The {program} was stopped before it finished")
+			}
+		}
 	}
 }
 
-pub mod shortcuts {
-	use std::process::Command;
+impl std::error::Error for InstallerError {}
+
+/// How to run the installer program of a package
+pub struct InstallerCommand {
+	technology: Option<InstallerTechnology>,
+	/// Arguments that are passed to the installer as-is
+	args: Vec<String>,
+	silent: bool,
+	/// Instruction to show before the installer starts, if it asks for input
+	instruction: Option<String>,
+}
+
+impl InstallerCommand {
+	/// Build the command for the installer of a package. Only installers with a known technology
+	/// can run silently, so others ask for input even if a silent install was asked for
+	fn new(
+		spec: &InstallSpec,
+		package: Package,
+		silent: bool,
+		paths: &PathContext,
+		out: &mut impl OutputTrait,
+	) -> Self {
+		let can_be_silent = spec.technology.is_some();
+		if silent && !can_be_silent {
+			out.warning(format!(
+				"Package {package} can't be installed silently. Its installer will ask for input"
+			));
+		}
+		let silent = silent && can_be_silent;
+
+		let mut args = Vec::new();
+		if let Some(technology) = spec.technology {
+			if silent {
+				args.extend(technology.get_silent_args().iter().map(|x| x.to_string()));
+				args.extend(spec.silent_args.iter().cloned());
+			}
+			// Some installers need the directory to be the last argument
+			if let Some(dir) = &spec.install_dir {
+				let dir = paths.expand(dir, package);
+				match technology.get_install_dir_arg(&dir) {
+					Some(arg) => args.push(arg),
+					None => out.warning(format!(
+						"The installer for package {package} can't change where it installs to"
+					)),
+				}
+			}
+		}
+
+		Self {
+			technology: spec.technology,
+			args,
+			silent,
+			instruction: spec.instruction.clone().filter(|_| !silent),
+		}
+	}
+
+	/// Check if the installer runs without asking for input
+	pub fn is_silent(&self) -> bool {
+		self.silent
+	}
+
+	/// Run an installer program and wait for it to finish, failing if it did not install the package
+	pub fn run(&self, path: &Path, data: &mut Data) -> anyhow::Result<()> {
+		self.show_instruction(data);
+		let status = self
+			.create(path)
+			.status()
+			.context("Failed to start installer")?;
+		self.check_status(status, data)
+	}
+
+	/// Show the instruction for the installer, if it has one
+	pub fn show_instruction(&self, data: &mut Data) {
+		if let Some(instruction) = &self.instruction {
+			data.out.instruction(instruction);
+		}
+	}
+
+	/// Check the exit status of an installer program, failing if it did not install the package
+	pub fn check_status(&self, status: ExitStatus, data: &mut Data) -> anyhow::Result<()> {
+		check_exit(self.technology, status, "installer", data)
+	}
+
+	/// Create the command that runs an installer program
+	pub fn create(&self, path: &Path) -> Command {
+		let mut cmd = if self.technology == Some(InstallerTechnology::Msi) {
+			let mut cmd = Command::new("msiexec");
+			cmd.arg("/i").arg(path);
+			cmd
+		} else {
+			Command::new(path)
+		};
+		self.add_args(&mut cmd);
+		cmd
+	}
+
+	/// Create the command that runs an installer program with administrator permissions
+	pub fn create_elevated(&self, path: &Path) -> anyhow::Result<Command> {
+		let mut cmd = run_elevated(path.to_owned())?;
+		self.add_args(&mut cmd);
+		Ok(cmd)
+	}
+
+	fn add_args(&self, cmd: &mut Command) {
+		// Install directory arguments have their own quoting rules, so none are quoted automatically
+		for arg in &self.args {
+			cmd.raw_arg(arg);
+		}
+	}
+}
+
+/// Check the exit status of an installer or uninstaller program, failing if it did not finish.
+/// The technology is the one that the package was installed with, since uninstallers are made by it too
+fn check_exit(
+	technology: Option<InstallerTechnology>,
+	status: ExitStatus,
+	program: &'static str,
+	data: &mut Data,
+) -> anyhow::Result<()> {
+	// Programs that were killed don't have an exit code
+	let code = status.code().ok_or(InstallerError::Failed(program, None))?;
+	let exit = match technology {
+		Some(technology) => technology.get_exit(code),
+		None => InstallerExit::from_code(code),
+	};
+
+	match exit {
+		InstallerExit::Success => Ok(()),
+		InstallerExit::RebootRequired => {
+			data.out.warning(format!(
+				"Restart the computer to finish the changes that the {program} made"
+			));
+			Ok(())
+		}
+		InstallerExit::Cancelled => Err(InstallerError::Cancelled(program).into()),
+		InstallerExit::Failed => Err(InstallerError::Failed(program, Some(code)).into()),
+	}
+}
+
+/// Get the installer file for a package and run it, returning the file that was installed
+pub async fn install(
+	spec: &InstallSpec,
+	package: Package,
+	from: &InstallFrom<'_>,
+	data: &mut Data<'_>,
+) -> anyhow::Result<ResolvedSource> {
+	let resolved = match from {
+		InstallFrom::Latest => {
+			data.out.progress("Finding installer");
+			spec.source
+				.resolve(&data.client, data.season, &mut *data.out)
+				.await
+				.context("Failed to find installer")?
+		}
+		InstallFrom::Locked(locked) | InstallFrom::File(_, locked) => locked.to_source(),
+	};
+
+	let path = match from {
+		InstallFrom::File(path, locked) => {
+			data.out.progress("Verifying installer");
+			let actual = file_sha256(path).context("Failed to read installer")?;
+			if !actual.eq_ignore_ascii_case(&locked.sha256) {
+				bail!(
+					"The installer at {} is corrupt. Expected SHA-256 checksum {} but got {actual}",
+					path.display(),
+					locked.sha256
+				);
+			}
+			path.clone()
+		}
+		_ => {
+			data.out.progress("Downloading installer");
+			download_installer(data, &resolved, &spec.get_file_name())
+				.await?
+				.path
+		}
+	};
+
+	let paths = PathContext::new(data)?;
+	let command = InstallerCommand::new(spec, package, data.silent, &paths, &mut *data.out);
+	run_method(&spec.method, &path, &command, package, data)?;
+
+	if let Some(configuration) = spec.configure {
+		data.out.progress("Configuring");
+		configure(configuration).context("Failed to configure")?;
+	}
+
+	Ok(resolved)
+}
 
-	use crate::data::Data;
+/// Install a package from its file using an install method
+fn run_method(
+	method: &InstallMethod,
+	path: &Path,
+	command: &InstallerCommand,
+	package: Package,
+	data: &mut Data,
+) -> anyhow::Result<()> {
+	match method {
+		InstallMethod::Run => methods::run(path, command, data),
+		InstallMethod::RunElevated => methods::run_elevated(path, command, data),
+		InstallMethod::ExtractArchive {
+			format,
+			dir,
+			strip_top_level,
+		} => {
+			let dir = PathContext::new(data)?.expand(dir, package);
+			methods::extract_archive(path, *format, &dir, *strip_top_level, data)
+		}
+		InstallMethod::ExtractIso { program } => {
+			let dir = package.get_data_path(data)?.join("image");
+			methods::extract_iso(path, program, &dir, command, data)
+		}
+	}
+}
+
+/// Apply a configuration built into the tool
+fn configure(configuration: Configuration) -> anyhow::Result<()> {
+	match configuration {
+		Configuration::AdvantageScope => advantagescope::configure(),
+	}
+}
+
+/// An installer file in the download cache
+pub struct CachedInstaller {
+	pub path: PathBuf,
+	/// SHA-256 checksum of the file in hex
+	pub sha256: String,
+}
+
+/// Get the file for an install source from the download cache, downloading it with the given
+/// file name if it is not cached yet. Sources without a checksum are always downloaded again. New
+/// files are checked against the source checksum if it has one, and are deleted if it does not
+/// match so that they are never run
+pub async fn download_installer(
+	data: &mut Data<'_>,
+	source: &ResolvedSource,
+	file_name: &str,
+) -> anyhow::Result<CachedInstaller> {
+	let cache = Cache::new(data)?;
+	if let (Some(path), Some(sha256)) = (cache.get(source)?, &source.sha256) {
+		data.out.progress("Using cached download");
+		return Ok(CachedInstaller {
+			path,
+			sha256: sha256.clone(),
+		});
+	}
+
+	let path = cache.get_download_path(source, file_name)?;
+	let actual = download_file(&data.client, &source.url, &path, &mut *data.out).await?;
+
+	if let Some(expected) = &source.sha256 {
+		if !actual.eq_ignore_ascii_case(expected) {
+			std::fs::remove_file(&path).context("Failed to delete corrupt file")?;
+			bail!(
+				"The download of {} is corrupt and was deleted. Expected SHA-256 checksum {expected} but got {actual}",
+				source.asset
+			);
+		}
+	} else {
+		data.out.warning(format!(
+			"No checksum is available for {}. Skipping verification",
+			source.asset
+		));
+	}
+
+	let path = cache.add(source, &path, &actual)?;
+
+	Ok(CachedInstaller {
+		path,
+		sha256: actual,
+	})
+}
+
+/// Launch a package using its launch target
+pub fn launch(target: &LaunchTarget, package: Package, data: &Data) -> anyhow::Result<()> {
+	let paths = PathContext::new(data)?;
+	match target {
+		LaunchTarget::Program {
+			path,
+			elevated,
+			in_directory,
+		} => {
+			let exec = paths.expand(path, package);
+			let mut cmd = if *elevated {
+				run_elevated(exec.clone())?
+			} else {
+				Command::new(&exec)
+			};
+			// Change the cwd for programs that keep their logs next to them
+			if *in_directory {
+				let cwd = exec.parent().context("Parent directory missing")?;
+				cmd.current_dir(cwd);
+			}
+			cmd.spawn()?;
+		}
+		LaunchTarget::Script { path } => {
+			let exec = paths.expand(path, package);
+			Command::new("cscript").arg(exec).spawn()?;
+		}
+		LaunchTarget::Url { url } => {
+			Command::new("cmd.exe")
+				.arg("/C")
+				.arg("start")
+				.arg(data.season.expand(url))
+				.spawn()?
+				.wait()?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Uninstall a package using its uninstall method, failing if the uninstaller did not finish
+pub fn uninstall(
+	method: &UninstallMethod,
+	technology: Option<InstallerTechnology>,
+	package: Package,
+	data: &mut Data,
+) -> anyhow::Result<()> {
+	let paths = PathContext::new(data)?;
+	match method {
+		UninstallMethod::Registered { display_name } => {
+			let uninstaller = find_registered_uninstaller(display_name)?
+				.ok_or(anyhow!("No uninstaller is registered for {display_name}"))?;
+			// Uninstall strings are full command lines, so they have to be passed through as-is
+			let status = Command::new("cmd.exe")
+				.arg("/C")
+				.raw_arg(uninstaller)
+				.status()
+				.context("Failed to start uninstaller")?;
+			check_exit(technology, status, "uninstaller", data)?;
+		}
+		UninstallMethod::Program { path } => {
+			let status = Command::new(paths.expand(path, package))
+				.status()
+				.context("Failed to start uninstaller")?;
+			check_exit(technology, status, "uninstaller", data)?;
+		}
+		UninstallMethod::Delete { path } => {
+			let path = paths.expand(path, package);
+			if path.exists() {
+				std::fs::remove_dir_all(&path).context("Failed to delete package directory")?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Look up the uninstall command for a program in the Windows registry. The display name of the
+/// program has to match exactly, except for a version number that many installers add to the end
+fn find_registered_uninstaller(display_name: &str) -> anyhow::Result<Option<String>> {
+	let name = display_name.replace('\'', "''");
+	let powershell_cmd = format!(
+		"Get-ItemProperty HKLM:/Software/Microsoft/Windows/CurrentVersion/Uninstall/*, HKLM:/Software/WOW6432Node/Microsoft/Windows/CurrentVersion/Uninstall/*, HKCU:/Software/Microsoft/Windows/CurrentVersion/Uninstall/* | Where-Object {{ $_.DisplayName -eq '{name}' -or $_.DisplayName -match ('^' + [regex]::Escape('{name}') + ' v?[0-9][0-9.]*$') }} | Select-Object -ExpandProperty UninstallString -Unique"
+	);
+	let output = Command::new("powershell.exe")
+		.arg("-command")
+		.arg(powershell_cmd)
+		.output()?;
+	let uninstallers =
+		String::from_utf8(output.stdout).context("Failed to convert uninstaller to UTF-8")?;
+	let uninstallers: Vec<_> = uninstallers
+		.lines()
+		.map(str::trim)
+		.filter(|x| !x.is_empty())
+		.collect();
+
+	match uninstallers.as_slice() {
+		[] => Ok(None),
+		[uninstaller] => Ok(Some(uninstaller.to_string())),
+		_ => bail!("More than one program named {display_name} is installed. Uninstall it manually from the Windows settings"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use serde_json::json;
+
+	use super::*;
+	use crate::output::NullOutput;
+	use crate::season::Season;
+
+	fn get_spec(technology: Option<&str>) -> InstallSpec {
+		serde_json::from_value(json!({
+			"source": { "type": "url", "url": "https://example.com/installer.exe" },
+			"method": { "type": "run" },
+			"technology": technology,
+			"install_dir": "{local_programs}/App",
+			"silent_args": ["/allusers"],
+			"instruction": "Select every component"
+		}))
+		.unwrap()
+	}
+
+	fn get_command(spec: &InstallSpec, silent: bool) -> InstallerCommand {
+		let paths = PathContext::with_root(Path::new("/fake"), Season::from_str("2024").unwrap());
+		let package = Package::from_str("pathplanner").unwrap();
+		InstallerCommand::new(spec, package, silent, &paths, &mut NullOutput)
+	}
+
+	fn get_install_dir() -> String {
+		Path::new("/fake/local_data/Programs/App")
+			.display()
+			.to_string()
+	}
 
-	pub fn open_wpilib_docs(_data: &mut Data) -> anyhow::Result<()> {
-		open_shortcut("https://docs.wpilib.org/en/stable/index.html")
+	#[test]
+	fn test_silent_args() {
+		let command = get_command(&get_spec(Some("nsis")), true);
+		assert!(command.is_silent());
+		assert_eq!(
+			command.args,
+			[
+				"/S".into(),
+				"/allusers".into(),
+				format!("/D={}", get_install_dir())
+			]
+		);
+		assert_eq!(command.instruction, None);
 	}
 
-	pub fn open_worbots_github(_data: &mut Data) -> anyhow::Result<()> {
-		open_shortcut("https://github.com/Worthington-Robotics")
+	#[test]
+	fn test_interactive_args() {
+		let command = get_command(&get_spec(Some("inno")), false);
+		assert!(!command.is_silent());
+		assert_eq!(command.args, [format!("/DIR=\"{}\"", get_install_dir())]);
+		assert_eq!(
+			command.instruction.as_deref(),
+			Some("Select every component")
+		);
 	}
 
-	pub fn open_game_manual(_data: &mut Data) -> anyhow::Result<()> {
-		open_shortcut("https://firstfrc.blob.core.windows.net/frc2023/Manual/2023FRCGameManual.pdf")
+	#[test]
+	fn test_unknown_technology_is_not_silent() {
+		let command = get_command(&get_spec(None), true);
+		assert!(!command.is_silent());
+		assert!(command.args.is_empty());
+		assert_eq!(
+			command.instruction.as_deref(),
+			Some("Select every component")
+		);
 	}
 
-	pub fn open_worbots_website(_data: &mut Data) -> anyhow::Result<()> {
-		open_shortcut("https://worbots4145.org/")
+	#[test]
+	fn test_install_dir_is_skipped_when_unsupported() {
+		let command = get_command(&get_spec(Some("squirrel")), true);
+		assert_eq!(command.args, ["--silent", "/allusers"]);
 	}
 
-	fn open_shortcut(url: &str) -> anyhow::Result<()> {
-		Command::new("cmd.exe")
-			.arg("/C")
-			.arg("start")
-			.arg(&url)
-			.spawn()?
-			.wait()?;
-		Ok(())
+	#[test]
+	fn test_msi_runs_with_msiexec() {
+		let command = get_command(&get_spec(Some("msi")), true);
+		let cmd = command.create(Path::new("installer.msi"));
+		assert_eq!(cmd.get_program(), "msiexec");
+		let args: Vec<_> = cmd
+			.get_args()
+			.map(|x| x.to_string_lossy().into_owned())
+			.collect();
+		assert_eq!(
+			args,
+			[
+				"/i".into(),
+				"installer.msi".into(),
+				"/qn".into(),
+				"/norestart".into(),
+				"/allusers".into(),
+				format!("INSTALLDIR=\"{}\"", get_install_dir())
+			]
+		);
 	}
 }
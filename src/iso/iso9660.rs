@@ -0,0 +1,130 @@
+use std::io::{Read, Seek};
+
+use anyhow::{bail, Context};
+
+use super::{
+	read_at, read_u32, slice, Entry, EntryKind, Extent, MAX_DEPTH, MAX_DIRECTORY_SIZE, SECTOR_SIZE,
+};
+
+/// Offset of the root directory record in a volume descriptor
+const ROOT_RECORD: usize = 156;
+/// Size of a directory record without its name
+const RECORD_HEADER_SIZE: usize = 33;
+/// Flag on a directory record for a directory
+const FLAG_DIRECTORY: u8 = 0x02;
+/// Flag on a directory record for a file that continues in the next record
+const FLAG_MULTI_EXTENT: u8 = 0x80;
+
+/// Check if a supplementary volume descriptor is for Joliet, which is marked by the escape
+/// sequence for one of the three levels of UCS-2
+pub fn is_joliet(descriptor: &[u8]) -> bool {
+	matches!(&descriptor[88..91], b"%/@" | b"%/C" | b"%/E")
+}
+
+/// Read the directory tree of the volume that a volume descriptor is for
+pub fn read_root<R: Read + Seek>(
+	reader: &mut R,
+	descriptor: &[u8],
+	joliet: bool,
+) -> anyhow::Result<Vec<Entry>> {
+	let record = slice(descriptor, ROOT_RECORD, 34)?;
+	let (position, length) = get_extent(record)?;
+	read_directory(reader, position, length, joliet, 0)
+}
+
+fn read_directory<R: Read + Seek>(
+	reader: &mut R,
+	position: u64,
+	length: u64,
+	joliet: bool,
+	depth: usize,
+) -> anyhow::Result<Vec<Entry>> {
+	if depth > MAX_DEPTH {
+		bail!("Directories are nested too deeply");
+	}
+	if length > MAX_DIRECTORY_SIZE {
+		bail!("Directory is too large");
+	}
+	let data = read_at(reader, position, length).context("Failed to read directory")?;
+
+	let mut out: Vec<Entry> = Vec::new();
+	// Whether the last record was for a file that continues in this one
+	let mut continued = false;
+	let mut offset = 0;
+	while offset < data.len() {
+		let record_len = data[offset] as usize;
+		// Records don't cross sectors, so the rest of a sector after the last one is zeros
+		if record_len == 0 {
+			offset = (offset / SECTOR_SIZE as usize + 1) * SECTOR_SIZE as usize;
+			continue;
+		}
+		if record_len < RECORD_HEADER_SIZE {
+			bail!("Directory record is too short");
+		}
+		let record = slice(&data, offset, record_len)?;
+		offset += record_len;
+
+		let name_len = record[32] as usize;
+		let name = slice(record, RECORD_HEADER_SIZE, name_len)?;
+		// The first two records are for the directory itself and its parent
+		if name == [0] || name == [1] {
+			continue;
+		}
+		let flags = record[25];
+		let (position, length) = get_extent(record)?;
+
+		if continued {
+			if let Some(Entry {
+				kind: EntryKind::File(extents),
+				..
+			}) = out.last_mut()
+			{
+				extents.push(Extent {
+					position: Some(position),
+					length,
+				});
+			}
+		} else {
+			let name = decode_name(name, joliet);
+			let kind = if flags & FLAG_DIRECTORY != 0 {
+				let children = read_directory(reader, position, length, joliet, depth + 1)
+					.with_context(|| format!("Failed to read directory {name}"))?;
+				EntryKind::Directory(children)
+			} else {
+				EntryKind::File(vec![Extent {
+					position: Some(position),
+					length,
+				}])
+			};
+			out.push(Entry { name, kind });
+		}
+		continued = flags & FLAG_MULTI_EXTENT != 0;
+	}
+
+	Ok(out)
+}
+
+/// Get the position and length of the data of a directory record
+fn get_extent(record: &[u8]) -> anyhow::Result<(u64, u64)> {
+	// The data comes after the extended attributes, which are a whole number of sectors
+	let extended_attributes = record[1] as u64;
+	let sector = read_u32(record, 2)? as u64 + extended_attributes;
+	let length = read_u32(record, 10)? as u64;
+	Ok((sector * SECTOR_SIZE, length))
+}
+
+/// Decode a file name, which is UCS-2 in Joliet and ASCII otherwise.
+/// The version number and the dot that files without an extension have are removed
+fn decode_name(name: &[u8], joliet: bool) -> String {
+	let name = if joliet {
+		let units: Vec<_> = name
+			.chunks_exact(2)
+			.map(|x| u16::from_be_bytes([x[0], x[1]]))
+			.collect();
+		String::from_utf16_lossy(&units)
+	} else {
+		String::from_utf8_lossy(name).into_owned()
+	};
+	let name = name.split(';').next().unwrap_or_default();
+	name.strip_suffix('.').unwrap_or(name).to_string()
+}
@@ -0,0 +1,749 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+
+mod iso9660;
+mod udf;
+
+/// Size of a sector in a disc image
+const SECTOR_SIZE: u64 = 2048;
+/// Sector that the volume descriptors start at. The sectors before it are reserved
+const VOLUME_DESCRIPTORS_START: u64 = 16;
+/// Most volume descriptors to look through before giving up
+const MAX_VOLUME_DESCRIPTORS: u64 = 64;
+/// Deepest directory nesting to read, so that a corrupt image with a loop in it can't recurse forever
+const MAX_DEPTH: usize = 64;
+/// Largest directory to read, so that a corrupt image can't make the tool run out of memory
+const MAX_DIRECTORY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// A filesystem in a disc image
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Filesystem {
+	/// Universal Disk Format, which most images made on Windows have
+	Udf,
+	/// The Joliet extension of ISO9660, which has long Unicode file names
+	Joliet,
+	/// Plain ISO9660, which only has short uppercase file names
+	Iso9660,
+}
+
+impl Display for Filesystem {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Udf => write!(f, "UDF"),
+			Self::Joliet => write!(f, "Joliet"),
+			Self::Iso9660 => write!(f, "ISO9660"),
+		}
+	}
+}
+
+/// A disc image whose files can be extracted without mounting it
+pub struct DiscImage<R> {
+	reader: R,
+	filesystem: Filesystem,
+	root: Vec<Entry>,
+}
+
+impl DiscImage<BufReader<File>> {
+	/// Open a disc image file
+	pub fn open(path: &Path) -> anyhow::Result<Self> {
+		let file = File::open(path).context("Failed to open image")?;
+		Self::new(BufReader::new(file))
+	}
+}
+
+impl<R: Read + Seek> DiscImage<R> {
+	/// Read the directory tree of a disc image. Images often have more than one filesystem with the
+	/// same files, so the one with the most complete file names is used
+	pub fn new(mut reader: R) -> anyhow::Result<Self> {
+		let volumes = find_volumes(&mut reader)?;
+
+		if volumes.udf {
+			let result = udf::read_root(&mut reader);
+			// Images that also have ISO9660 can still be read if their UDF filesystem uses
+			// features that aren't supported
+			if result.is_ok() || (volumes.joliet.is_none() && volumes.primary.is_none()) {
+				return Ok(Self {
+					root: result.context("Failed to read UDF filesystem")?,
+					reader,
+					filesystem: Filesystem::Udf,
+				});
+			}
+		}
+
+		let (filesystem, root) = match (volumes.joliet, volumes.primary) {
+			(Some(descriptor), _) => (
+				Filesystem::Joliet,
+				iso9660::read_root(&mut reader, &descriptor, true),
+			),
+			(None, Some(descriptor)) => (
+				Filesystem::Iso9660,
+				iso9660::read_root(&mut reader, &descriptor, false),
+			),
+			(None, None) => bail!("The file is not a disc image"),
+		};
+
+		Ok(Self {
+			root: root.with_context(|| format!("Failed to read {filesystem} filesystem"))?,
+			reader,
+			filesystem,
+		})
+	}
+
+	/// Get the filesystem that the files are read from
+	pub fn filesystem(&self) -> Filesystem {
+		self.filesystem
+	}
+
+	/// Extract every file in the image into a directory
+	pub fn extract(&mut self, dir: &Path) -> anyhow::Result<()> {
+		std::fs::create_dir_all(dir).context("Failed to create directory")?;
+		extract_entries(&mut self.reader, &self.root, dir)
+	}
+}
+
+/// A file or directory in a disc image
+struct Entry {
+	name: String,
+	kind: EntryKind,
+}
+
+enum EntryKind {
+	File(Vec<Extent>),
+	Directory(Vec<Entry>),
+}
+
+/// A run of bytes that makes up part of a file
+struct Extent {
+	/// Byte offset of the run in the image, or None if it is not recorded and reads as zeros
+	position: Option<u64>,
+	length: u64,
+}
+
+/// Volume descriptors found at the start of an image
+#[derive(Default)]
+struct Volumes {
+	/// The ISO9660 primary volume descriptor
+	primary: Option<Vec<u8>>,
+	/// The ISO9660 supplementary volume descriptor for Joliet
+	joliet: Option<Vec<u8>>,
+	/// Whether the image says that it has a UDF filesystem
+	udf: bool,
+}
+
+/// Read the volume descriptors of an image. ISO9660 descriptors come first, followed by the
+/// descriptors that mark a UDF filesystem
+fn find_volumes<R: Read + Seek>(reader: &mut R) -> anyhow::Result<Volumes> {
+	let mut out = Volumes::default();
+	for sector in VOLUME_DESCRIPTORS_START..VOLUME_DESCRIPTORS_START + MAX_VOLUME_DESCRIPTORS {
+		let descriptor = match read_at(reader, sector * SECTOR_SIZE, SECTOR_SIZE) {
+			Ok(descriptor) => descriptor,
+			Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+			Err(e) => return Err(e).context("Failed to read volume descriptor"),
+		};
+		match &descriptor[1..6] {
+			b"CD001" => match descriptor[0] {
+				1 if out.primary.is_none() => out.primary = Some(descriptor),
+				2 if out.joliet.is_none() && iso9660::is_joliet(&descriptor) => {
+					out.joliet = Some(descriptor)
+				}
+				_ => {}
+			},
+			b"NSR02" | b"NSR03" => out.udf = true,
+			b"BEA01" | b"TEA01" | b"BOOT2" | b"CDW02" => {}
+			_ => break,
+		}
+	}
+
+	Ok(out)
+}
+
+fn extract_entries<R: Read + Seek>(
+	reader: &mut R,
+	entries: &[Entry],
+	dir: &Path,
+) -> anyhow::Result<()> {
+	for entry in entries {
+		check_name(&entry.name)?;
+		let path = dir.join(&entry.name);
+		match &entry.kind {
+			EntryKind::Directory(children) => {
+				std::fs::create_dir_all(&path)
+					.with_context(|| format!("Failed to create directory {}", path.display()))?;
+				extract_entries(reader, children, &path)?;
+			}
+			EntryKind::File(extents) => {
+				let file = File::create(&path)
+					.with_context(|| format!("Failed to create file {}", path.display()))?;
+				let mut file = BufWriter::new(file);
+				for extent in extents {
+					copy_extent(reader, extent, &mut file)
+						.with_context(|| format!("Failed to extract file {}", path.display()))?;
+				}
+				file.flush()?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Check that a name from the image can't write outside of the directory it is extracted to
+fn check_name(name: &str) -> anyhow::Result<()> {
+	if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', ':', '\0']) {
+		bail!("The image contains a file with the unsafe name {name:?}");
+	}
+	Ok(())
+}
+
+fn copy_extent<R: Read + Seek>(
+	reader: &mut R,
+	extent: &Extent,
+	out: &mut impl Write,
+) -> std::io::Result<()> {
+	let copied = match extent.position {
+		Some(position) => {
+			reader.seek(SeekFrom::Start(position))?;
+			std::io::copy(&mut reader.by_ref().take(extent.length), out)?
+		}
+		None => std::io::copy(&mut std::io::repeat(0).take(extent.length), out)?,
+	};
+	if copied < extent.length {
+		return Err(std::io::Error::new(
+			ErrorKind::UnexpectedEof,
+			"The image ends in the middle of a file",
+		));
+	}
+
+	Ok(())
+}
+
+/// Read bytes from a position in the image
+fn read_at<R: Read + Seek>(reader: &mut R, position: u64, length: u64) -> std::io::Result<Vec<u8>> {
+	reader.seek(SeekFrom::Start(position))?;
+	let mut out = vec![0; length as usize];
+	reader.read_exact(&mut out)?;
+	Ok(out)
+}
+
+/// Read the data of a file made of extents
+fn read_extents<R: Read + Seek>(reader: &mut R, extents: &[Extent]) -> anyhow::Result<Vec<u8>> {
+	let mut out = Vec::new();
+	for extent in extents {
+		copy_extent(reader, extent, &mut out)?;
+	}
+	Ok(out)
+}
+
+/// Get a field from a structure in the image, failing if the structure is cut off
+fn field<const N: usize>(buf: &[u8], pos: usize) -> anyhow::Result<[u8; N]> {
+	buf.get(pos..pos + N)
+		.and_then(|x| x.try_into().ok())
+		.ok_or(anyhow!("A structure in the image is cut off"))
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> anyhow::Result<u16> {
+	Ok(u16::from_le_bytes(field(buf, pos)?))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> anyhow::Result<u32> {
+	Ok(u32::from_le_bytes(field(buf, pos)?))
+}
+
+fn read_u64(buf: &[u8], pos: usize) -> anyhow::Result<u64> {
+	Ok(u64::from_le_bytes(field(buf, pos)?))
+}
+
+/// Get a range of bytes from a structure in the image, failing if the structure is cut off
+fn slice(buf: &[u8], pos: usize, len: usize) -> anyhow::Result<&[u8]> {
+	buf.get(pos..pos + len)
+		.ok_or(anyhow!("A structure in the image is cut off"))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use tempfile::TempDir;
+
+	use super::*;
+
+	// Flags of ISO9660 directory records
+	const DIRECTORY: u8 = 0x02;
+	const MULTI_EXTENT: u8 = 0x80;
+
+	// UDF descriptor tags
+	const TAG_ANCHOR: u16 = 2;
+	const TAG_PARTITION: u16 = 5;
+	const TAG_LOGICAL_VOLUME: u16 = 6;
+	const TAG_TERMINATING: u16 = 8;
+	const TAG_FILE_SET: u16 = 256;
+	const TAG_FILE_IDENTIFIER: u16 = 257;
+	const TAG_FILE_ENTRY: u16 = 261;
+	const TAG_EXTENDED_FILE_ENTRY: u16 = 266;
+
+	/// Sector that the UDF partition starts at
+	const PARTITION_START: u64 = 300;
+
+	/// A disc image that is built in memory
+	#[derive(Default)]
+	struct Image(Vec<u8>);
+
+	impl Image {
+		/// Write bytes at the start of a sector, growing the image to a whole number of sectors
+		fn write(&mut self, sector: u64, bytes: &[u8]) {
+			let start = (sector * SECTOR_SIZE) as usize;
+			let end = start + bytes.len();
+			let len = end.div_ceil(SECTOR_SIZE as usize) * SECTOR_SIZE as usize;
+			if self.0.len() < len {
+				self.0.resize(len, 0);
+			}
+			self.0[start..end].copy_from_slice(bytes);
+		}
+
+		/// Write bytes at the start of a block in the UDF partition
+		fn write_block(&mut self, block: u32, bytes: &[u8]) {
+			self.write(PARTITION_START + block as u64, bytes);
+		}
+
+		fn open(self) -> anyhow::Result<DiscImage<Cursor<Vec<u8>>>> {
+			DiscImage::new(Cursor::new(self.0))
+		}
+	}
+
+	fn put(buf: &mut [u8], pos: usize, bytes: &[u8]) {
+		buf[pos..pos + bytes.len()].copy_from_slice(bytes);
+	}
+
+	/// The contents of the files in the test images
+	const README: &[u8] = b"Read me\n";
+	const INNER: &[u8] = b"inner";
+
+	/// A file that is split into two extents with unrelated data between them
+	fn get_big_file() -> Vec<u8> {
+		let mut out = vec![b'a'; SECTOR_SIZE as usize];
+		out.extend([b'b'; 100]);
+		out
+	}
+
+	/// Write the data of the ISO9660 files, which both directory trees point to
+	fn write_iso_files(image: &mut Image) {
+		image.write(40, README);
+		image.write(41, &[b'a'; SECTOR_SIZE as usize]);
+		image.write(42, &[b'x'; SECTOR_SIZE as usize]);
+		image.write(43, &[b'b'; 100]);
+		image.write(44, INNER);
+		image.write(45, b"no extension");
+	}
+
+	fn iso_record(name: &[u8], sector: u32, length: u32, flags: u8) -> Vec<u8> {
+		// Records have an even length
+		let len = 33 + name.len() + (name.len() + 1) % 2;
+		let mut out = vec![0; len];
+		out[0] = len as u8;
+		put(&mut out, 2, &sector.to_le_bytes());
+		put(&mut out, 6, &sector.to_be_bytes());
+		put(&mut out, 10, &length.to_le_bytes());
+		put(&mut out, 14, &length.to_be_bytes());
+		out[25] = flags;
+		out[32] = name.len() as u8;
+		put(&mut out, 33, name);
+		out
+	}
+
+	/// Write an ISO9660 directory, which starts with the records for itself and its parent
+	fn write_iso_directory(image: &mut Image, sector: u32, records: &[Vec<u8>]) {
+		let mut data = iso_record(&[0], sector, SECTOR_SIZE as u32, DIRECTORY);
+		data.extend(iso_record(&[1], sector, SECTOR_SIZE as u32, DIRECTORY));
+		data.extend(records.concat());
+		image.write(sector as u64, &data);
+	}
+
+	fn iso_descriptor(kind: u8, root: u32, joliet: bool) -> Vec<u8> {
+		let mut out = vec![0; SECTOR_SIZE as usize];
+		out[0] = kind;
+		put(&mut out, 1, b"CD001");
+		out[6] = 1;
+		if joliet {
+			put(&mut out, 88, b"%/E");
+		}
+		put(
+			&mut out,
+			156,
+			&iso_record(&[0], root, SECTOR_SIZE as u32, DIRECTORY),
+		);
+		out
+	}
+
+	fn joliet_name(name: &str) -> Vec<u8> {
+		name.encode_utf16().flat_map(u16::to_be_bytes).collect()
+	}
+
+	/// Write ISO9660 volume descriptors and directories, with a Joliet tree of long names if asked.
+	/// Returns the next free volume descriptor sector
+	fn write_iso(image: &mut Image, joliet: bool) -> u64 {
+		write_iso_files(image);
+		write_iso_directory(
+			image,
+			24,
+			&[
+				iso_record(b"README.TXT;1", 40, README.len() as u32, 0),
+				iso_record(b"BIG.BIN;1", 41, SECTOR_SIZE as u32, MULTI_EXTENT),
+				iso_record(b"BIG.BIN;1", 43, 100, 0),
+				iso_record(b"DIR", 25, SECTOR_SIZE as u32, DIRECTORY),
+				iso_record(b"NOEXT.;1", 45, 12, 0),
+			],
+		);
+		write_iso_directory(
+			image,
+			25,
+			&[iso_record(b"INNER.TXT;1", 44, INNER.len() as u32, 0)],
+		);
+		image.write(16, &iso_descriptor(1, 24, false));
+
+		let mut next = 17;
+		if joliet {
+			write_iso_directory(
+				image,
+				26,
+				&[
+					iso_record(
+						&joliet_name("Read Me First.txt;1"),
+						40,
+						README.len() as u32,
+						0,
+					),
+					iso_record(
+						&joliet_name("Big File.bin;1"),
+						41,
+						SECTOR_SIZE as u32,
+						MULTI_EXTENT,
+					),
+					iso_record(&joliet_name("Big File.bin;1"), 43, 100, 0),
+					iso_record(
+						&joliet_name("A Directory"),
+						27,
+						SECTOR_SIZE as u32,
+						DIRECTORY,
+					),
+				],
+			);
+			write_iso_directory(
+				image,
+				27,
+				&[iso_record(
+					&joliet_name("Inner File.txt;1"),
+					44,
+					INNER.len() as u32,
+					0,
+				)],
+			);
+			image.write(next, &iso_descriptor(2, 26, true));
+			next += 1;
+		}
+
+		let mut terminator = vec![0; SECTOR_SIZE as usize];
+		terminator[0] = 255;
+		put(&mut terminator, 1, b"CD001");
+		image.write(next, &terminator);
+		next + 1
+	}
+
+	fn udf_descriptor(tag: u16) -> Vec<u8> {
+		let mut out = vec![0; SECTOR_SIZE as usize];
+		put(&mut out, 0, &tag.to_le_bytes());
+		out
+	}
+
+	/// Where the data of a UDF file is
+	enum Allocation<'a> {
+		/// Short allocation descriptors of a length with its extent type and a block
+		Short(&'a [(u32, u32)]),
+		/// Data that is stored in the file entry
+		Embedded(&'a [u8]),
+	}
+
+	fn udf_file_entry(tag: u16, file_type: u8, size: usize, allocation: Allocation) -> Vec<u8> {
+		let mut out = udf_descriptor(tag);
+		let header = if tag == TAG_EXTENDED_FILE_ENTRY {
+			216
+		} else {
+			176
+		};
+		let (flags, descriptors) = match allocation {
+			Allocation::Short(extents) => {
+				let descriptors: Vec<_> = extents
+					.iter()
+					.flat_map(|(length, block)| [length.to_le_bytes(), block.to_le_bytes()])
+					.flatten()
+					.collect();
+				(0u16, descriptors)
+			}
+			Allocation::Embedded(data) => (3, data.to_vec()),
+		};
+		out[27] = file_type;
+		put(&mut out, 34, &flags.to_le_bytes());
+		put(&mut out, 56, &(size as u64).to_le_bytes());
+		// The length of the descriptors comes right before them
+		put(
+			&mut out,
+			header - 4,
+			&(descriptors.len() as u32).to_le_bytes(),
+		);
+		put(&mut out, header, &descriptors);
+		out
+	}
+
+	/// A UDF file identifier. Names that aren't Latin-1 are stored as UTF-16
+	fn udf_identifier(name: Option<&str>, characteristics: u8, block: u32) -> Vec<u8> {
+		let name = match name {
+			None => Vec::new(),
+			Some(name) if name.chars().all(|x| (x as u32) < 256) => {
+				let mut out = vec![8];
+				out.extend(name.chars().map(|x| x as u8));
+				out
+			}
+			Some(name) => {
+				let mut out = vec![16];
+				out.extend(name.encode_utf16().flat_map(u16::to_be_bytes));
+				out
+			}
+		};
+		let mut out = vec![0; (38 + name.len() + 3) & !3];
+		put(&mut out, 0, &TAG_FILE_IDENTIFIER.to_le_bytes());
+		out[18] = characteristics;
+		out[19] = name.len() as u8;
+		put(&mut out, 20, &(SECTOR_SIZE as u32).to_le_bytes());
+		put(&mut out, 24, &block.to_le_bytes());
+		put(&mut out, 38, &name);
+		out
+	}
+
+	/// Write a UDF directory and the file entry for it
+	fn write_udf_directory(image: &mut Image, entry: u32, block: u32, identifiers: &[Vec<u8>]) {
+		let data = identifiers.concat();
+		let extents = [(data.len() as u32, block)];
+		let file_entry = udf_file_entry(TAG_FILE_ENTRY, 4, data.len(), Allocation::Short(&extents));
+		image.write_block(entry, &file_entry);
+		image.write_block(block, &data);
+	}
+
+	/// Write a UDF filesystem with its volume recognition sequence at a sector
+	fn write_udf(image: &mut Image, sequence_start: u64) {
+		for (i, identifier) in [b"BEA01", b"NSR02", b"TEA01"].iter().enumerate() {
+			let mut descriptor = vec![0; SECTOR_SIZE as usize];
+			put(&mut descriptor, 1, *identifier);
+			image.write(sequence_start + i as u64, &descriptor);
+		}
+
+		let mut anchor = udf_descriptor(TAG_ANCHOR);
+		put(&mut anchor, 16, &(3 * SECTOR_SIZE as u32).to_le_bytes());
+		put(&mut anchor, 20, &32u32.to_le_bytes());
+		image.write(256, &anchor);
+
+		let mut partition = udf_descriptor(TAG_PARTITION);
+		put(&mut partition, 188, &(PARTITION_START as u32).to_le_bytes());
+		image.write(32, &partition);
+		let mut logical_volume = udf_descriptor(TAG_LOGICAL_VOLUME);
+		put(
+			&mut logical_volume,
+			212,
+			&(SECTOR_SIZE as u32).to_le_bytes(),
+		);
+		put(&mut logical_volume, 268, &1u32.to_le_bytes());
+		// A physical partition map for partition 0
+		put(&mut logical_volume, 440, &[1, 6]);
+		image.write(33, &logical_volume);
+		image.write(34, &udf_descriptor(TAG_TERMINATING));
+
+		let mut file_set = udf_descriptor(TAG_FILE_SET);
+		put(&mut file_set, 404, &1u32.to_le_bytes());
+		image.write_block(0, &file_set);
+
+		write_udf_directory(
+			image,
+			1,
+			2,
+			&[
+				udf_identifier(None, 0x08, 1),
+				udf_identifier(Some("Read Me First.txt"), 0, 3),
+				udf_identifier(Some("Big File.bin"), 0, 4),
+				udf_identifier(Some("Ünïcödé ✓.txt"), 0, 5),
+				udf_identifier(Some("A Directory"), 0x02, 6),
+			],
+		);
+		write_udf_directory(
+			image,
+			6,
+			7,
+			&[
+				udf_identifier(None, 0x08, 1),
+				udf_identifier(Some("Inner File.txt"), 0, 8),
+			],
+		);
+
+		let extents = [(README.len() as u32, 10)];
+		let readme = udf_file_entry(TAG_FILE_ENTRY, 5, README.len(), Allocation::Short(&extents));
+		image.write_block(3, &readme);
+		image.write_block(10, README);
+
+		// A recorded block, an unrecorded one that reads as zeros, and a padded last block
+		let extents = [
+			(SECTOR_SIZE as u32, 11),
+			(1 << 30 | SECTOR_SIZE as u32, 0),
+			(SECTOR_SIZE as u32, 13),
+		];
+		let big = udf_file_entry(
+			TAG_EXTENDED_FILE_ENTRY,
+			5,
+			2 * SECTOR_SIZE as usize + 100,
+			Allocation::Short(&extents),
+		);
+		image.write_block(4, &big);
+		image.write_block(11, &[b'a'; SECTOR_SIZE as usize]);
+		image.write_block(12, &[b'x'; SECTOR_SIZE as usize]);
+		let mut last = vec![b'b'; 100];
+		last.resize(SECTOR_SIZE as usize, b'x');
+		image.write_block(13, &last);
+
+		let unicode = udf_file_entry(TAG_FILE_ENTRY, 5, 7, Allocation::Embedded(b"unicode"));
+		image.write_block(5, &unicode);
+
+		let extents = [(INNER.len() as u32, 14)];
+		let inner = udf_file_entry(TAG_FILE_ENTRY, 5, INNER.len(), Allocation::Short(&extents));
+		image.write_block(8, &inner);
+		image.write_block(14, INNER);
+	}
+
+	fn extract(image: &mut DiscImage<Cursor<Vec<u8>>>) -> TempDir {
+		let dir = tempfile::tempdir().unwrap();
+		image.extract(dir.path()).unwrap();
+		dir
+	}
+
+	/// Get the sorted names of the files in a directory
+	fn list(dir: &Path) -> Vec<String> {
+		let mut out: Vec<_> = std::fs::read_dir(dir)
+			.unwrap()
+			.map(|x| x.unwrap().file_name().to_string_lossy().into_owned())
+			.collect();
+		out.sort();
+		out
+	}
+
+	fn read(dir: &Path, path: &str) -> Vec<u8> {
+		std::fs::read(dir.join(path)).unwrap()
+	}
+
+	/// Check the files of an image with long names
+	fn check_long_names(dir: &Path) {
+		assert_eq!(read(dir, "Read Me First.txt"), README);
+		assert_eq!(read(dir, "A Directory/Inner File.txt"), INNER);
+	}
+
+	#[test]
+	fn test_iso9660() {
+		let mut image = Image::default();
+		write_iso(&mut image, false);
+		let mut image = image.open().unwrap();
+		assert_eq!(image.filesystem(), Filesystem::Iso9660);
+
+		let dir = extract(&mut image);
+		let dir = dir.path();
+		assert_eq!(list(dir), ["BIG.BIN", "DIR", "NOEXT", "README.TXT"]);
+		assert_eq!(read(dir, "README.TXT"), README);
+		assert_eq!(read(dir, "BIG.BIN"), get_big_file());
+		assert_eq!(read(dir, "DIR/INNER.TXT"), INNER);
+		assert_eq!(read(dir, "NOEXT"), b"no extension");
+	}
+
+	#[test]
+	fn test_joliet() {
+		let mut image = Image::default();
+		write_iso(&mut image, true);
+		let mut image = image.open().unwrap();
+		assert_eq!(image.filesystem(), Filesystem::Joliet);
+
+		let dir = extract(&mut image);
+		let dir = dir.path();
+		assert_eq!(
+			list(dir),
+			["A Directory", "Big File.bin", "Read Me First.txt"]
+		);
+		check_long_names(dir);
+		assert_eq!(read(dir, "Big File.bin"), get_big_file());
+	}
+
+	#[test]
+	fn test_udf() {
+		let mut image = Image::default();
+		write_udf(&mut image, 16);
+		let mut image = image.open().unwrap();
+		assert_eq!(image.filesystem(), Filesystem::Udf);
+
+		let dir = extract(&mut image);
+		let dir = dir.path();
+		assert_eq!(
+			list(dir),
+			[
+				"A Directory",
+				"Big File.bin",
+				"Read Me First.txt",
+				"Ünïcödé ✓.txt"
+			]
+		);
+		check_long_names(dir);
+		assert_eq!(read(dir, "Ünïcödé ✓.txt"), b"unicode");
+
+		let mut big = vec![b'a'; SECTOR_SIZE as usize];
+		big.extend([0; SECTOR_SIZE as usize]);
+		big.extend([b'b'; 100]);
+		assert_eq!(read(dir, "Big File.bin"), big);
+	}
+
+	#[test]
+	fn test_udf_is_preferred() {
+		let mut image = Image::default();
+		let next = write_iso(&mut image, true);
+		write_udf(&mut image, next);
+		assert_eq!(image.open().unwrap().filesystem(), Filesystem::Udf);
+	}
+
+	#[test]
+	fn test_broken_udf_falls_back() {
+		let mut image = Image::default();
+		let next = write_iso(&mut image, true);
+		write_udf(&mut image, next);
+		image.write(256, &[0; SECTOR_SIZE as usize]);
+
+		let mut image = image.open().unwrap();
+		assert_eq!(image.filesystem(), Filesystem::Joliet);
+		check_long_names(extract(&mut image).path());
+	}
+
+	#[test]
+	fn test_not_an_image() {
+		let mut image = Image::default();
+		image.write(20, &[0; SECTOR_SIZE as usize]);
+		assert!(image.open().is_err());
+	}
+
+	#[test]
+	fn test_unsafe_name() {
+		let mut image = Image::default();
+		write_iso(&mut image, false);
+		write_iso_directory(
+			&mut image,
+			24,
+			&[iso_record(b"../EVIL.TXT;1", 40, README.len() as u32, 0)],
+		);
+
+		let parent = tempfile::tempdir().unwrap();
+		let dir = parent.path().join("extracted");
+		assert!(image.open().unwrap().extract(&dir).is_err());
+		assert!(!parent.path().join("EVIL.TXT").exists());
+	}
+}
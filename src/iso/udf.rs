@@ -0,0 +1,426 @@
+use std::io::{Read, Seek};
+
+use anyhow::{anyhow, bail, Context};
+
+use super::{
+	read_at, read_extents, read_u16, read_u32, read_u64, slice, Entry, EntryKind, Extent,
+	MAX_DEPTH, MAX_DIRECTORY_SIZE, SECTOR_SIZE,
+};
+
+/// Sector of the anchor that points to the volume descriptors
+const ANCHOR_SECTOR: u64 = 256;
+/// Largest logical block size to read. Images always use the sector size
+const MAX_BLOCK_SIZE: u64 = 64 * 1024;
+/// Most allocation extent descriptors to follow for one file, so that a loop can't go on forever
+const MAX_ALLOCATION_EXTENTS: usize = 1024;
+
+// Identifiers of descriptor tags
+const TAG_ANCHOR: u16 = 2;
+const TAG_PARTITION: u16 = 5;
+const TAG_LOGICAL_VOLUME: u16 = 6;
+const TAG_TERMINATING: u16 = 8;
+const TAG_FILE_SET: u16 = 256;
+const TAG_FILE_IDENTIFIER: u16 = 257;
+const TAG_ALLOCATION_EXTENT: u16 = 258;
+const TAG_FILE_ENTRY: u16 = 261;
+const TAG_EXTENDED_FILE_ENTRY: u16 = 266;
+
+// File types in the ICB tag of a file entry
+const FILE_TYPE_DIRECTORY: u8 = 4;
+const FILE_TYPE_FILE: u8 = 5;
+
+// Characteristics of a file identifier descriptor
+const CHARACTERISTIC_DELETED: u8 = 0x04;
+const CHARACTERISTIC_PARENT: u8 = 0x08;
+
+/// Size of a file identifier descriptor without its implementation use and name
+const FILE_IDENTIFIER_HEADER_SIZE: usize = 38;
+
+/// Read the directory tree of the UDF filesystem in an image
+pub fn read_root<R: Read + Seek>(reader: &mut R) -> anyhow::Result<Vec<Entry>> {
+	let volume = Volume::read(reader)?;
+	let file_set = volume.read_block(reader, volume.file_set)?;
+	check_tag(&file_set, TAG_FILE_SET)?;
+	let root = read_long_ad(&file_set, 400)?;
+
+	let file = volume.read_file_entry(reader, root)?;
+	if file.file_type != FILE_TYPE_DIRECTORY {
+		bail!("The root of the filesystem is not a directory");
+	}
+	volume.read_directory(reader, &file.extents, 0)
+}
+
+/// Location of a logical block in a partition
+#[derive(Clone, Copy)]
+struct BlockAddress {
+	block: u32,
+	/// Index of the partition in the partition maps of the logical volume
+	partition: u16,
+}
+
+/// How logical blocks in a partition are found in the image
+enum Partition {
+	/// The blocks are in order from a starting position
+	Physical { start: u64 },
+	/// The blocks are in the data of a metadata file, which is stored in another partition
+	Metadata { extents: Vec<Extent> },
+}
+
+/// The logical volume that holds the filesystem
+struct Volume {
+	block_size: u64,
+	partitions: Vec<Partition>,
+	/// Location of the file set descriptor, which points to the root directory
+	file_set: BlockAddress,
+}
+
+/// The parts of a file entry that are needed to read the file
+struct FileEntry {
+	file_type: u8,
+	extents: Vec<Extent>,
+}
+
+impl Volume {
+	/// Find the logical volume and its partitions using the volume descriptors
+	fn read<R: Read + Seek>(reader: &mut R) -> anyhow::Result<Self> {
+		let anchor = read_at(reader, ANCHOR_SECTOR * SECTOR_SIZE, SECTOR_SIZE)
+			.context("Failed to read anchor")?;
+		check_tag(&anchor, TAG_ANCHOR)?;
+		let sequence_length = read_u32(&anchor, 16)? as u64;
+		let sequence_start = read_u32(&anchor, 20)? as u64;
+
+		// Partition descriptors are keyed by their partition number
+		let mut partition_starts = Vec::new();
+		let mut logical_volume = None;
+		for sector in sequence_start..sequence_start + sequence_length / SECTOR_SIZE {
+			let descriptor = read_at(reader, sector * SECTOR_SIZE, SECTOR_SIZE)
+				.context("Failed to read volume descriptor")?;
+			match read_u16(&descriptor, 0)? {
+				TAG_PARTITION => {
+					let number = read_u16(&descriptor, 22)?;
+					let start = read_u32(&descriptor, 188)? as u64;
+					partition_starts.push((number, start));
+				}
+				TAG_LOGICAL_VOLUME if logical_volume.is_none() => logical_volume = Some(descriptor),
+				TAG_TERMINATING => break,
+				_ => {}
+			}
+		}
+		let logical_volume = logical_volume.ok_or(anyhow!("No logical volume found"))?;
+
+		let block_size = read_u32(&logical_volume, 212)? as u64;
+		if block_size == 0 || block_size > MAX_BLOCK_SIZE {
+			bail!("Unsupported block size {block_size}");
+		}
+		let file_set = read_long_ad(&logical_volume, 248)?;
+
+		let map_count = read_u32(&logical_volume, 268)?;
+		let mut out = Self {
+			block_size,
+			partitions: Vec::new(),
+			file_set,
+		};
+		// Metadata partitions are set up after the others since their files are stored in them
+		let mut metadata_files = Vec::new();
+		let mut offset = 440;
+		for _ in 0..map_count {
+			let map_type = *logical_volume.get(offset).unwrap_or(&0);
+			let map_length = *logical_volume.get(offset + 1).unwrap_or(&0) as usize;
+			if map_length == 0 {
+				bail!("Partition map is empty");
+			}
+			let map = slice(&logical_volume, offset, map_length)?;
+			offset += map_length;
+
+			let (number, metadata_file) = match map_type {
+				1 => (read_u16(map, 4)?, None),
+				2 => {
+					let identifier = slice(map, 5, 23)?;
+					let number = read_u16(map, 38)?;
+					if identifier.starts_with(b"*UDF Metadata Partition") {
+						(number, Some(read_u32(map, 40)?))
+					} else if identifier.starts_with(b"*UDF Sparable Partition") {
+						// Spared blocks only exist on rewritable discs, not on images
+						(number, None)
+					} else {
+						bail!(
+							"Unsupported partition type {}",
+							String::from_utf8_lossy(identifier).trim_end_matches('\0')
+						);
+					}
+				}
+				_ => bail!("Unknown partition map type {map_type}"),
+			};
+
+			let start = partition_starts
+				.iter()
+				.find(|x| x.0 == number)
+				.ok_or(anyhow!("Partition {number} is missing"))?
+				.1;
+			out.partitions.push(Partition::Physical {
+				start: start * block_size,
+			});
+			if let Some(block) = metadata_file {
+				metadata_files.push((out.partitions.len() - 1, block));
+			}
+		}
+
+		// The metadata file is in the physical partition that the map refers to
+		for (index, block) in metadata_files {
+			let address = BlockAddress {
+				block,
+				partition: index as u16,
+			};
+			let file = out
+				.read_file_entry(reader, address)
+				.context("Failed to read metadata file")?;
+			out.partitions[index] = Partition::Metadata {
+				extents: file.extents,
+			};
+		}
+
+		Ok(out)
+	}
+
+	/// Get the position of a logical block in the image
+	fn get_position(&self, address: BlockAddress) -> anyhow::Result<u64> {
+		let partition = self
+			.partitions
+			.get(address.partition as usize)
+			.ok_or(anyhow!("Partition {} does not exist", address.partition))?;
+		let offset = address.block as u64 * self.block_size;
+		match partition {
+			Partition::Physical { start } => Ok(start + offset),
+			Partition::Metadata { extents } => {
+				let mut extent_start = 0;
+				for extent in extents {
+					if offset < extent_start + extent.length {
+						let position = extent
+							.position
+							.ok_or(anyhow!("Metadata block {} is not recorded", address.block))?;
+						return Ok(position + offset - extent_start);
+					}
+					extent_start += extent.length;
+				}
+				bail!(
+					"Metadata block {} is past the end of the metadata",
+					address.block
+				)
+			}
+		}
+	}
+
+	fn read_block<R: Read + Seek>(
+		&self,
+		reader: &mut R,
+		address: BlockAddress,
+	) -> anyhow::Result<Vec<u8>> {
+		let position = self.get_position(address)?;
+		read_at(reader, position, self.block_size).context("Failed to read block")
+	}
+
+	/// Read a file entry and the extents that the data of its file is in
+	fn read_file_entry<R: Read + Seek>(
+		&self,
+		reader: &mut R,
+		address: BlockAddress,
+	) -> anyhow::Result<FileEntry> {
+		let entry = self.read_block(reader, address)?;
+		let (attributes_length, descriptors_length, header) = match read_u16(&entry, 0)? {
+			TAG_FILE_ENTRY => (read_u32(&entry, 168)?, read_u32(&entry, 172)?, 176),
+			TAG_EXTENDED_FILE_ENTRY => (read_u32(&entry, 208)?, read_u32(&entry, 212)?, 216),
+			tag => bail!("Expected a file entry but found descriptor {tag}"),
+		};
+		let file_type = entry[27];
+		let flags = read_u16(&entry, 34)?;
+		let size = read_u64(&entry, 56)?;
+
+		let descriptors_start = header + attributes_length as usize;
+		let descriptors = slice(&entry, descriptors_start, descriptors_length as usize)?;
+		let mut extents = Vec::new();
+		match flags & 0x07 {
+			// Small files are stored in the entry itself
+			3 => extents.push(Extent {
+				position: Some(self.get_position(address)? + descriptors_start as u64),
+				length: descriptors_length as u64,
+			}),
+			kind => {
+				self.read_allocation_descriptors(reader, descriptors, kind, address, &mut extents)?
+			}
+		}
+
+		// The last extent is usually padded to a whole block
+		let mut remaining = size;
+		for extent in &mut extents {
+			extent.length = extent.length.min(remaining);
+			remaining -= extent.length;
+		}
+		extents.retain(|x| x.length > 0);
+		if remaining > 0 {
+			extents.push(Extent {
+				position: None,
+				length: remaining,
+			});
+		}
+
+		Ok(FileEntry { file_type, extents })
+	}
+
+	/// Read the descriptors that list where the data of a file is. Extents that are short allocation
+	/// descriptors are in the same partition as the entry that lists them
+	fn read_allocation_descriptors<R: Read + Seek>(
+		&self,
+		reader: &mut R,
+		descriptors: &[u8],
+		kind: u16,
+		entry: BlockAddress,
+		out: &mut Vec<Extent>,
+	) -> anyhow::Result<()> {
+		let mut descriptors = descriptors.to_vec();
+		let mut entry = entry;
+		for _ in 0..MAX_ALLOCATION_EXTENTS {
+			let size = match kind {
+				0 => 8,
+				1 => 16,
+				2 => 20,
+				_ => bail!("Unknown allocation descriptor type {kind}"),
+			};
+
+			let mut next = None;
+			for descriptor in descriptors.chunks_exact(size) {
+				let raw_length = read_u32(descriptor, 0)?;
+				let length = (raw_length & 0x3FFF_FFFF) as u64;
+				if length == 0 {
+					break;
+				}
+				let address = match kind {
+					0 => BlockAddress {
+						block: read_u32(descriptor, 4)?,
+						partition: entry.partition,
+					},
+					1 => read_long_ad(descriptor, 0)?,
+					_ => BlockAddress {
+						block: read_u32(descriptor, 12)?,
+						partition: read_u16(descriptor, 16)?,
+					},
+				};
+
+				match raw_length >> 30 {
+					0 => out.push(Extent {
+						position: Some(self.get_position(address)?),
+						length,
+					}),
+					// Allocated and unallocated extents that were never written read as zeros
+					1 | 2 => out.push(Extent {
+						position: None,
+						length,
+					}),
+					// The rest of the descriptors are in an allocation extent descriptor
+					_ => {
+						next = Some(address);
+						break;
+					}
+				}
+			}
+
+			let Some(address) = next else {
+				return Ok(());
+			};
+			let block = self.read_block(reader, address)?;
+			check_tag(&block, TAG_ALLOCATION_EXTENT)?;
+			let length = read_u32(&block, 20)? as usize;
+			descriptors = slice(&block, 24, length)?.to_vec();
+			entry = address;
+		}
+
+		bail!("File has too many allocation extents")
+	}
+
+	fn read_directory<R: Read + Seek>(
+		&self,
+		reader: &mut R,
+		extents: &[Extent],
+		depth: usize,
+	) -> anyhow::Result<Vec<Entry>> {
+		if depth > MAX_DEPTH {
+			bail!("Directories are nested too deeply");
+		}
+		if extents.iter().map(|x| x.length).sum::<u64>() > MAX_DIRECTORY_SIZE {
+			bail!("Directory is too large");
+		}
+		let data = read_extents(reader, extents).context("Failed to read directory")?;
+
+		let mut out = Vec::new();
+		let mut offset = 0;
+		while offset + FILE_IDENTIFIER_HEADER_SIZE <= data.len() {
+			let identifier = &data[offset..];
+			check_tag(identifier, TAG_FILE_IDENTIFIER)?;
+			let characteristics = identifier[18];
+			let name_length = identifier[19] as usize;
+			let address = read_long_ad(identifier, 20)?;
+			let use_length = read_u16(identifier, 36)? as usize;
+			let name = slice(
+				identifier,
+				FILE_IDENTIFIER_HEADER_SIZE + use_length,
+				name_length,
+			)?;
+			// Descriptors are padded to a multiple of four bytes
+			offset += (FILE_IDENTIFIER_HEADER_SIZE + use_length + name_length + 3) & !3;
+
+			if characteristics & (CHARACTERISTIC_DELETED | CHARACTERISTIC_PARENT) != 0 {
+				continue;
+			}
+			let name = decode_name(name)?;
+			let file = self
+				.read_file_entry(reader, address)
+				.with_context(|| format!("Failed to read file entry for {name}"))?;
+			let kind = match file.file_type {
+				FILE_TYPE_DIRECTORY => EntryKind::Directory(
+					self.read_directory(reader, &file.extents, depth + 1)
+						.with_context(|| format!("Failed to read directory {name}"))?,
+				),
+				FILE_TYPE_FILE => EntryKind::File(file.extents),
+				// Links and other special files are skipped
+				_ => continue,
+			};
+			out.push(Entry { name, kind });
+		}
+
+		Ok(out)
+	}
+}
+
+/// Check the identifier of a descriptor tag
+fn check_tag(descriptor: &[u8], expected: u16) -> anyhow::Result<()> {
+	let tag = read_u16(descriptor, 0)?;
+	if tag != expected {
+		bail!("Expected descriptor {expected} but found descriptor {tag}");
+	}
+	Ok(())
+}
+
+/// Read the address in a long allocation descriptor
+fn read_long_ad(buf: &[u8], pos: usize) -> anyhow::Result<BlockAddress> {
+	Ok(BlockAddress {
+		block: read_u32(buf, pos + 4)?,
+		partition: read_u16(buf, pos + 8)?,
+	})
+}
+
+/// Decode a file name, whose first byte says if it is Latin-1 or UTF-16
+fn decode_name(name: &[u8]) -> anyhow::Result<String> {
+	let Some((&compression, name)) = name.split_first() else {
+		bail!("File name is empty");
+	};
+	match compression {
+		8 | 254 => Ok(name.iter().map(|&x| x as char).collect()),
+		16 | 255 => {
+			let units: Vec<_> = name
+				.chunks_exact(2)
+				.map(|x| u16::from_be_bytes([x[0], x[1]]))
+				.collect();
+			Ok(String::from_utf16_lossy(&units))
+		}
+		_ => bail!("Unknown file name compression {compression}"),
+	}
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::data::Data;
+use crate::install::download_installer;
+use crate::manifest::ResolvedSource;
+use crate::output::OutputTrait;
+use crate::package::Package;
+use crate::season::Season;
+
+/// Name of the default lockfile in the data directory
+pub const LOCKFILE: &str = "packages.lock.json";
+
+/// Exact files to install for each package, so that every computer on the team
+/// installs the same versions
+#[derive(Serialize, Deserialize)]
+pub struct Lockfile {
+	/// Version of the lockfile format
+	pub version: u32,
+	/// The season that the packages were resolved for
+	pub season: Season,
+	/// Locked files, keyed by package ID
+	pub packages: BTreeMap<String, LockedPackage>,
+}
+
+/// The locked file for a single package
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LockedPackage {
+	/// The release tag, if the source has one
+	pub tag: Option<String>,
+	/// File name of the asset
+	pub asset: String,
+	pub url: String,
+	/// SHA-256 checksum of the file in hex
+	pub sha256: String,
+}
+
+impl Lockfile {
+	/// The current version of the lockfile format
+	pub const VERSION: u32 = 1;
+
+	/// Create an empty lockfile for a season
+	pub fn new(season: Season) -> Self {
+		Self {
+			version: Self::VERSION,
+			season,
+			packages: BTreeMap::new(),
+		}
+	}
+
+	/// Load a lockfile
+	pub fn load(path: &Path) -> anyhow::Result<Self> {
+		let contents = std::fs::read_to_string(path).context("Failed to read lockfile")?;
+		let out: Self = serde_json::from_str(&contents).context("Failed to parse lockfile")?;
+		if out.version > Self::VERSION {
+			bail!(
+				"Lockfile has version {}, but this tool only supports up to version {}. Update the tool",
+				out.version,
+				Self::VERSION
+			);
+		}
+
+		Ok(out)
+	}
+
+	/// Write the lockfile to disk
+	pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+		let contents = serde_json::to_string_pretty(self)?;
+		std::fs::write(path, contents).context("Failed to write lockfile")?;
+		Ok(())
+	}
+
+	/// Get the locked file for a package
+	pub fn get(&self, package: Package) -> Option<&LockedPackage> {
+		self.packages.get(package.id())
+	}
+
+	/// Resolve the latest files for packages in the season of the lockfile and lock them.
+	/// Files without a published checksum are downloaded into the download cache to find it,
+	/// so installing from the lockfile afterwards does not download them again
+	pub async fn lock(&mut self, packages: &[Package], data: &mut Data<'_>) -> anyhow::Result<()> {
+		for package in packages {
+			data.out.progress(format!("Locking package {package}"));
+			let (Some(spec), Some(source)) = (
+				package.get_install_spec(),
+				package.resolve_source(data, self.season).await?,
+			) else {
+				continue;
+			};
+			// Only download the file if its checksum is not published
+			let sha256 = match &source.sha256 {
+				Some(sha256) => sha256.clone(),
+				None => {
+					data.out
+						.progress(format!("Downloading {} to find its checksum", source.asset));
+					package
+						.with_network(download_installer(data, &source, &spec.get_file_name()))
+						.await
+						.with_context(|| format!("Failed to checksum package {package}"))?
+						.sha256
+				}
+			};
+			self.packages.insert(
+				package.id().to_string(),
+				LockedPackage {
+					tag: source.version,
+					asset: source.asset,
+					url: source.url,
+					sha256,
+				},
+			);
+		}
+
+		Ok(())
+	}
+}
+
+impl LockedPackage {
+	/// Get the source to install the locked file from
+	pub fn to_source(&self) -> ResolvedSource {
+		ResolvedSource {
+			url: self.url.clone(),
+			asset: self.asset.clone(),
+			version: self.tag.clone(),
+			sha256: Some(self.sha256.clone()),
+			selection: None,
+		}
+	}
+}
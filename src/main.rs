@@ -1,22 +1,61 @@
 #![cfg_attr(release, windows_subsystem = "windows")]
 
-use anyhow::Context;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use bundle::Bundle;
+use cache::Cache;
+use chrono::Local;
 use clap::Parser;
 use color_print::{cformat, cprintln};
-use data::Data;
-use output::CommonOutput;
-use package::{Package, ALL_PACKAGES};
+use data::{Data, PersistentData};
+use install::InstallFrom;
+use lockfile::{Lockfile, LOCKFILE};
+use manifest::Manifest;
+use net::{check_endpoints, set_network_policy};
+use output::{format_bytes, CommonOutput};
+use package::{get_default_packages, init_registry, resolve_install_order, Package, ALL_PACKAGES};
+use paths::PathContext;
+use probe::DetectedVersion;
+use profile::{get_profile, get_profiles};
+use season::Season;
+use serve::serve;
+use team_manifest::{get_cached_team_manifest, get_team_manifest};
 use ui::start_app;
+use update::find_outdated;
 
-use crate::utils::tokio_exec;
+use crate::utils::{
+	get_mirror, set_github_settings, set_mirror, tokio_exec, GithubSettings, GITHUB_CACHE_DIR,
+	GITHUB_TOKEN_VAR,
+};
 
+mod archive;
 mod assets;
+mod bundle;
+mod cache;
+mod config;
 mod data;
+mod dependencies;
 mod install;
+mod iso;
+mod lockfile;
+mod manifest;
+mod net;
 mod output;
 mod package;
+mod paths;
+mod probe;
+mod profile;
+mod season;
+mod select;
+mod serve;
+mod team_manifest;
 mod ui;
+mod update;
 mod utils;
+mod version;
 
 #[cfg(not(target_os = "windows"))]
 compile_error!("This tool is Windows-only");
@@ -29,62 +68,258 @@ fn main() {
 }
 
 fn run_cli() -> anyhow::Result<()> {
+	// Package names are looked up after parsing, since the registry is only loaded for the
+	// commands that use packages
 	let cli = Cli::parse();
+
 	let mut out = CommonOutput;
 	let mut data = Data::new(&mut out).context("Failed to create application data")?;
+	set_mirror(
+		cli.mirror
+			.clone()
+			.or_else(|| data.config.mirror_url.clone()),
+	);
+	set_network_policy(data.config.network.clone());
+	set_github_settings(GithubSettings {
+		token: data
+			.config
+			.github_token
+			.clone()
+			.or_else(|| std::env::var(GITHUB_TOKEN_VAR).ok()),
+		cache_dir: Some(data.get_data_directory()?.join(GITHUB_CACHE_DIR)),
+	});
+	if let Some(season) = cli.season {
+		data.season = season;
+	}
+	data.silent = cli.silent;
+
+	if cli.command.uses_packages() {
+		load_registry(&cli.command, &mut data)?;
+	}
+
 	match cli.command {
 		Subcommand::App => {
 			println!("Starting app");
 			start_app().context("Failed to start app")?;
 		}
-		Subcommand::List => {
+		Subcommand::List { profile: None } => {
 			cprintln!("<s>Available packages:");
-			for pkg in ALL_PACKAGES {
-				cprintln!(
-					" - <s><b>{}</b> ({})</>: {}",
-					pkg.display_name(),
-					pkg,
-					pkg.short_description()
-				);
+			list_packages(&ALL_PACKAGES);
+		}
+		Subcommand::List {
+			profile: Some(None),
+		} => {
+			cprintln!("<s>Available profiles:");
+			for (name, packages) in get_profiles(&data.config) {
+				cprintln!(" - <s><b>{}</b></>: {}", name, packages.join(", "));
 			}
 		}
-		Subcommand::Install { packages } => {
-			install_packages(packages, &mut data)?;
+		Subcommand::List {
+			profile: Some(Some(profile)),
+		} => {
+			let packages = get_profile(&data.config, &profile)?;
+			cprintln!("<s>Packages in profile {profile}:");
+			list_packages(&packages);
+		}
+		Subcommand::Install {
+			packages,
+			profile,
+			locked,
+			lockfile,
+			from_bundle,
+		} => {
+			let mut packages = parse_packages(&packages)?;
+			if let Some(profile) = profile {
+				packages.extend(get_profile(&data.config, &profile)?);
+			}
+			if let Some(dir) = from_bundle {
+				let bundle = Bundle::load(&dir)?;
+				// Install everything in the bundle if no packages are given
+				if packages.is_empty() {
+					packages = bundle.get_packages()?;
+				}
+				use_lockfile_season(&bundle.lockfile, &mut data);
+				install_packages(packages, &mut data, InstallMode::Bundle(&bundle))?;
+			} else if locked {
+				let path = get_lockfile_path(&data, lockfile)?;
+				let lockfile = Lockfile::load(&path)?;
+				use_lockfile_season(&lockfile, &mut data);
+				install_packages(packages, &mut data, InstallMode::Locked(&lockfile))?;
+			} else {
+				install_packages(packages, &mut data, InstallMode::Latest)?;
+			}
 		}
 		Subcommand::InstallAll => {
-			// Ordered so that more important packages are installed first
-			install_packages(
-				vec![
-					Package::WPILib,
-					Package::Phoenix,
-					Package::GithubDesktop,
-					Package::REVClient,
-					Package::AdvantageScope,
-					Package::Etcher,
-					Package::GRIP,
-					Package::PathPlanner,
-					Package::LimelightFinder,
-				],
-				&mut data,
-			)?;
+			install_packages(get_default_packages(), &mut data, InstallMode::Latest)?;
 		}
 		Subcommand::Launch { packages } => {
-			launch_packages(packages, &mut data)?;
+			launch_packages(parse_packages(&packages)?, &mut data)?;
+		}
+		Subcommand::Lock {
+			packages,
+			profile,
+			lockfile,
+		} => {
+			let mut packages = parse_packages(&packages)?;
+			if let Some(profile) = profile {
+				packages.extend(get_profile(&data.config, &profile)?);
+			}
+			let path = get_lockfile_path(&data, lockfile)?;
+			lock_packages(packages, &path, &mut data)?;
+		}
+		Subcommand::Explain { packages } => {
+			explain_packages(parse_packages(&packages)?, &mut data)?;
+		}
+		Subcommand::Bundle {
+			command: BundleCommand::Export {
+				dir,
+				packages,
+				profile,
+			},
+		} => {
+			let mut packages = parse_packages(&packages)?;
+			if let Some(profile) = profile {
+				packages.extend(get_profile(&data.config, &profile)?);
+			}
+			export_bundle(packages, &dir, &mut data)?;
+		}
+		Subcommand::Cache { command } => {
+			manage_cache(command, &data)?;
+		}
+		Subcommand::Manifest { command } => {
+			manage_team_manifest(command)?;
+		}
+		Subcommand::Net {
+			command: NetCommand::Check,
+		} => {
+			check_network(&data)?;
+		}
+		Subcommand::Serve { address } => {
+			cprintln!("<s>Serving the download cache at http://{address}");
+			cprintln!("<s>Other computers can download from it using --mirror http://<<this computer>>:<<port>>");
+			serve(Cache::new(&data)?, &address)?;
+		}
+		Subcommand::Uninstall { packages } => {
+			uninstall_packages(parse_packages(&packages)?, &mut data)?;
+		}
+		Subcommand::Status { root } => {
+			print_status(&data, root.as_deref())?;
+		}
+		Subcommand::Outdated => {
+			let outdated = tokio_exec(find_outdated(&mut data))??;
+			if outdated.is_empty() {
+				cprintln!("<s,g>All packages are up to date");
+			} else {
+				cprintln!("<s>Outdated packages:");
+				for pkg in outdated {
+					cprintln!(
+						" - <s><b>{}</b> ({})</>: {} -> {}",
+						pkg.package.display_name(),
+						pkg.package,
+						pkg.installed,
+						pkg.latest
+					);
+				}
+			}
+		}
+		Subcommand::Update => {
+			let outdated = tokio_exec(find_outdated(&mut data))??;
+			if outdated.is_empty() {
+				cprintln!("<s,g>All packages are up to date");
+			} else {
+				let packages = outdated.into_iter().map(|x| x.package).collect();
+				install_packages(packages, &mut data, InstallMode::Latest)?;
+			}
 		}
 	}
 
 	Ok(())
 }
 
-fn install_packages(packages: Vec<Package>, data: &mut Data) -> anyhow::Result<()> {
+fn list_packages(packages: &[Package]) {
+	for pkg in packages {
+		cprintln!(
+			" - <s><b>{}</b> ({})</>: {}",
+			pkg.display_name(),
+			pkg,
+			pkg.short_description()
+		);
+	}
+}
+
+/// Where install_packages gets the files for packages from
+enum InstallMode<'a> {
+	Latest,
+	Locked(&'a Lockfile),
+	Bundle(&'a Bundle),
+}
+
+impl<'a> InstallMode<'a> {
+	/// Get the file source for a package, or None if the package is missing
+	fn get(&self, package: Package) -> Option<InstallFrom<'a>> {
+		match self {
+			Self::Latest => Some(InstallFrom::Latest),
+			Self::Locked(lockfile) => lockfile.get(package).map(InstallFrom::Locked),
+			Self::Bundle(bundle) => bundle.get(package),
+		}
+	}
+}
+
+fn install_packages(
+	packages: Vec<Package>,
+	data: &mut Data,
+	mode: InstallMode,
+) -> anyhow::Result<()> {
+	let mut order = resolve_install_order(&packages).context("Failed to resolve dependencies")?;
+	// Only install dependencies that are missing
+	let paths = PathContext::new(data)?;
+	let persistent = PersistentData::load(data)?;
+	order.retain(|x| packages.contains(x) || !x.is_installed(&paths, &persistent));
+
+	let dependencies: Vec<_> = order
+		.iter()
+		.filter(|x| x.can_install() && !packages.contains(x))
+		.map(ToString::to_string)
+		.collect();
+	if !dependencies.is_empty() {
+		cprintln!(
+			"<s>Also installing dependencies: {}",
+			dependencies.join(", ")
+		);
+	}
+
+	// Check that every file is available before installing anything
+	let mut steps = Vec::new();
+	for package in order {
+		if !package.can_install() {
+			steps.push((package, None));
+			continue;
+		}
+		let Some(from) = mode.get(package) else {
+			match mode {
+				InstallMode::Bundle(..) => bail!(
+					"Package {package} is not in the bundle. Export the bundle again with it included"
+				),
+				_ => {
+					bail!("Package {package} is not in the lockfile. Add it with the lock command")
+				}
+			}
+		};
+		steps.push((package, Some(from)));
+	}
+
 	tokio_exec(async {
-		for package in packages {
-			if package.can_install() {
-				package.install(data).await?;
+		for (package, from) in steps {
+			if let Some(from) = from {
+				package
+					.install(data, &from)
+					.await
+					.with_context(|| format!("Failed to install package {package}"))?;
 			} else {
 				if let Some(parent) = package.get_parent() {
+					// The parent was installed earlier since it is a dependency
 					cprintln!(
-						"<r>This package cannot be installed as it is part of the package {parent}"
+						"<s>The package {package} was installed as part of the package {parent}"
 					);
 				} else {
 					cprintln!(
@@ -101,6 +336,131 @@ fn install_packages(packages: Vec<Package>, data: &mut Data) -> anyhow::Result<(
 	Ok(())
 }
 
+fn lock_packages(packages: Vec<Package>, path: &Path, data: &mut Data) -> anyhow::Result<()> {
+	// Lock everything if no packages are given
+	let packages = if packages.is_empty() {
+		ALL_PACKAGES
+			.iter()
+			.filter(|x| x.can_install())
+			.copied()
+			.collect()
+	} else {
+		packages
+	};
+	let order = resolve_install_order(&packages).context("Failed to resolve dependencies")?;
+
+	// Update the existing lockfile if it is for the same season
+	let mut lockfile = match path.exists() {
+		true => Lockfile::load(path)?,
+		false => Lockfile::new(data.season),
+	};
+	if lockfile.season != data.season {
+		lockfile = Lockfile::new(data.season);
+	}
+
+	tokio_exec(lockfile.lock(&order, data))??;
+	lockfile.save(path)?;
+
+	cprintln!("<s,g>Locked packages to {}", path.display());
+
+	Ok(())
+}
+
+fn explain_packages(packages: Vec<Package>, data: &mut Data) -> anyhow::Result<()> {
+	for pkg in packages {
+		let season = data.season;
+		let Some(source) = tokio_exec(pkg.resolve_source(data, season))?? else {
+			cprintln!(
+				"<s><b>{}</b> ({})</> cannot be installed",
+				pkg.display_name(),
+				pkg
+			);
+			continue;
+		};
+		cprintln!(
+			"<s><b>{}</b> ({})</>: {}",
+			pkg.display_name(),
+			pkg,
+			source.asset
+		);
+		match &source.selection {
+			Some(selection) => println!("{selection}"),
+			None => println!("Downloaded from {}", source.url),
+		}
+	}
+
+	Ok(())
+}
+
+/// Load the package registry from the team manifest and the local overrides
+fn load_registry(command: &Subcommand, data: &mut Data) -> anyhow::Result<()> {
+	let team_manifest = if command.uses_cached_manifest() {
+		get_cached_team_manifest(data)?
+	} else {
+		tokio_exec(get_team_manifest(data))??
+	};
+	let manifest = Manifest::load(data.get_data_directory()?, team_manifest.as_deref())
+		.context("Failed to load package manifest")?;
+	init_registry(manifest);
+
+	Ok(())
+}
+
+/// Look up packages by name. The registry has to be loaded first
+fn parse_packages(names: &[String]) -> anyhow::Result<Vec<Package>> {
+	names.iter().map(|x| Package::from_str(x)).collect()
+}
+
+/// Install packages for the season of a lockfile
+fn use_lockfile_season(lockfile: &Lockfile, data: &mut Data) {
+	if lockfile.season != data.season {
+		cprintln!("<s>Using season {} from the lockfile", lockfile.season);
+		data.season = lockfile.season;
+	}
+}
+
+fn export_bundle(packages: Vec<Package>, dir: &Path, data: &mut Data) -> anyhow::Result<()> {
+	if packages.is_empty() {
+		bail!("No packages to bundle. Give the names of the packages or a profile");
+	}
+	let order = resolve_install_order(&packages).context("Failed to resolve dependencies")?;
+	tokio_exec(Bundle::export(&order, dir, data))??;
+
+	cprintln!("<s,g>Exported bundle to {}", dir.display());
+
+	Ok(())
+}
+
+fn get_lockfile_path(data: &Data, path: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+	match path {
+		Some(path) => Ok(path),
+		None => Ok(data.get_data_directory()?.join(LOCKFILE)),
+	}
+}
+
+fn uninstall_packages(packages: Vec<Package>, data: &mut Data) -> anyhow::Result<()> {
+	tokio_exec(async {
+		for package in packages {
+			if package.can_install() {
+				package.uninstall(data).await?;
+			} else if let Some(parent) = package.get_parent() {
+				cprintln!(
+					"<r>This package is part of the package {parent}. Uninstall that instead"
+				);
+			} else {
+				cprintln!(
+					"<r>This package cannot be uninstalled as it is not installed by this tool"
+				);
+			}
+		}
+		Ok::<(), anyhow::Error>(())
+	})??;
+
+	cprintln!("<s,g>All packages uninstalled");
+
+	Ok(())
+}
+
 fn launch_packages(packages: Vec<Package>, data: &mut Data) -> anyhow::Result<()> {
 	tokio_exec(async {
 		for package in packages {
@@ -118,10 +478,182 @@ fn launch_packages(packages: Vec<Package>, data: &mut Data) -> anyhow::Result<()
 	Ok(())
 }
 
+fn manage_cache(command: CacheCommand, data: &Data) -> anyhow::Result<()> {
+	let cache = Cache::new(data)?;
+	match command {
+		CacheCommand::List => {
+			let entries = cache.list()?;
+			if entries.is_empty() {
+				cprintln!("<s>The download cache is empty");
+			}
+			for entry in entries {
+				let time = entry.last_used.with_timezone(&Local);
+				cprintln!(
+					" - <s><b>{}</b></>: {}, last used {}",
+					entry.asset,
+					format_bytes(entry.size as f64),
+					time.format("%Y-%m-%d %H:%M")
+				);
+				cprintln!("   from {}", entry.url);
+			}
+		}
+		CacheCommand::Size => {
+			let size = format_bytes(cache.size()? as f64);
+			cprintln!("<s>The download cache is using {size}");
+		}
+		CacheCommand::Prune { keep } => {
+			let removed = cache.prune(keep)?;
+			for entry in &removed {
+				cprintln!(" - Removed <s>{}</>", entry.asset);
+			}
+			cprintln!("<s,g>Removed {} cached downloads", removed.len());
+		}
+		CacheCommand::Clear => {
+			cache.clear()?;
+			cprintln!("<s,g>Cleared the download cache");
+		}
+	}
+
+	Ok(())
+}
+
+fn manage_team_manifest(command: ManifestCommand) -> anyhow::Result<()> {
+	match command {
+		ManifestCommand::Keygen { key } => {
+			let public_key = team_manifest::keygen(&key)?;
+			cprintln!("<s,g>Saved the private key to {}", key.display());
+			cprintln!("<s>Keep it private, since anyone with it can sign team manifests");
+			cprintln!("<s>Put this public key in src/assets/team_manifest.pub and build the tool:");
+			println!("{public_key}");
+		}
+		ManifestCommand::Sign { manifest, key } => {
+			let signed = team_manifest::sign(&manifest, &key)?;
+			if !signed.trusted {
+				cprintln!("<s,y>This key is not the one built into this tool, so the manifest will be rejected until the tool is built with its public key");
+			}
+			cprintln!(
+				"<s,g>Signed the manifest. Upload it and {} to the manifest URL",
+				signed.signature_path.display()
+			);
+		}
+	}
+
+	Ok(())
+}
+
+fn check_network(data: &Data) -> anyhow::Result<()> {
+	let mut endpoints = BTreeSet::from([
+		"https://api.github.com".to_string(),
+		"https://objects.githubusercontent.com".to_string(),
+	]);
+	endpoints.extend(data.config.manifest_url.clone());
+	endpoints.extend(get_mirror());
+	endpoints.extend(
+		ALL_PACKAGES
+			.iter()
+			.filter_map(|x| x.get_endpoint(data.season)),
+	);
+
+	cprintln!("<s>Checking {} servers...", endpoints.len());
+	let statuses = tokio_exec(check_endpoints(
+		&data.client,
+		endpoints.into_iter().collect(),
+	))?;
+	for status in &statuses {
+		match &status.result {
+			Ok((code, time)) => cprintln!(
+				" - <s>{}</>: <g>reachable</> ({code}, {} ms)",
+				status.url,
+				time.as_millis()
+			),
+			Err(e) => cprintln!(
+				" - <s>{}</>: <r>unreachable</> ({})",
+				status.url,
+				e.root_cause()
+			),
+		}
+	}
+
+	if statuses.iter().any(|x| x.is_certificate_error()) {
+		cprintln!(
+			"<s,y>Some servers have certificates that are not trusted. If your network inspects HTTPS traffic, add its root certificate to ca_bundles in the config"
+		);
+	}
+	let unreachable = statuses.iter().filter(|x| x.result.is_err()).count();
+	if unreachable == 0 {
+		cprintln!("<s,g>All servers are reachable");
+	} else {
+		cprintln!("<s,r>{unreachable} servers could not be reached");
+	}
+
+	Ok(())
+}
+
+fn print_status(data: &Data, root: Option<&Path>) -> anyhow::Result<()> {
+	let paths = match root {
+		Some(root) => PathContext::with_root(root, data.season),
+		None => PathContext::new(data)?,
+	};
+	cprintln!("<s>Detected packages for season {}:", data.season);
+	for pkg in ALL_PACKAGES.iter().filter(|x| x.has_version_probe()) {
+		let version = match pkg.detect_version(&paths) {
+			Some(DetectedVersion::Version(version)) => version,
+			Some(DetectedVersion::Unknown) => "unknown version".into(),
+			Some(DetectedVersion::NotInstalled) | None => continue,
+		};
+		cprintln!(
+			" - <s><b>{}</b> ({})</>: {}",
+			pkg.display_name(),
+			pkg,
+			version
+		);
+	}
+
+	let persistent = PersistentData::load(data)?;
+	if persistent.installed_packages.is_empty() {
+		cprintln!("<s>No packages have been installed by this tool");
+		return Ok(());
+	}
+
+	cprintln!("<s>Installed by this tool:");
+	for installed in &persistent.installed_packages {
+		let name = match Package::from_str(&installed.package) {
+			Ok(pkg) => format!("{} ({})", pkg.display_name(), pkg),
+			Err(..) => installed.package.clone(),
+		};
+		let name = match installed.season {
+			Some(season) => format!("{name} [{season}]"),
+			None => name,
+		};
+		let version = installed.version.as_deref().unwrap_or("unknown version");
+		let time = installed.installed_at.with_timezone(&Local);
+		cprintln!(
+			" - <s><b>{}</b></>: {} installed {}",
+			name,
+			version,
+			time.format("%Y-%m-%d %H:%M")
+		);
+		if let Some(path) = &installed.install_path {
+			cprintln!("   at {}", path.display());
+		}
+	}
+
+	Ok(())
+}
+
 #[derive(Parser)]
 struct Cli {
 	#[clap(subcommand)]
 	command: Subcommand,
+	/// The season to use instead of the current one, such as 2023
+	#[arg(long, global = true)]
+	season: Option<Season>,
+	/// URL of another computer running the serve command to download files from first
+	#[arg(long, global = true)]
+	mirror: Option<String>,
+	/// Run installers without asking for input, for the packages that support it
+	#[arg(long, global = true)]
+	silent: bool,
 }
 
 #[derive(clap::Subcommand)]
@@ -129,17 +661,168 @@ enum Subcommand {
 	/// Opens the graphical application
 	App,
 	/// Lists available packages
-	List,
+	List {
+		/// Only list the packages in this profile. Lists the available profiles if no name is given
+		#[arg(long)]
+		profile: Option<Option<String>>,
+	},
 	/// Installs a package
 	Install {
 		/// The names of the packages to install
-		packages: Vec<Package>,
+		packages: Vec<String>,
+		/// The name of a profile to install the packages of
+		#[arg(long)]
+		profile: Option<String>,
+		/// Install the exact files in the lockfile instead of the latest releases
+		#[arg(long)]
+		locked: bool,
+		/// Path to the lockfile. Defaults to the lockfile in the data directory
+		#[arg(long, requires = "locked")]
+		lockfile: Option<PathBuf>,
+		/// Install from the files in a bundle directory without using the network.
+		/// Installs everything in the bundle if no packages are given
+		#[arg(long, conflicts_with = "locked")]
+		from_bundle: Option<PathBuf>,
 	},
-	/// Installs all available packages
+	/// Installs the default set of packages that most computers need
 	InstallAll,
+	/// Resolves the latest releases of packages and records them in the lockfile.
+	/// Locks all packages if none are given
+	Lock {
+		/// The names of the packages to lock
+		packages: Vec<String>,
+		/// The name of a profile to lock the packages of
+		#[arg(long)]
+		profile: Option<String>,
+		/// Path to the lockfile. Defaults to the lockfile in the data directory
+		#[arg(long)]
+		lockfile: Option<PathBuf>,
+	},
+	/// Shows the file that would be installed for packages and why it was chosen
+	Explain {
+		/// The names of the packages to explain
+		packages: Vec<String>,
+	},
+	/// Manages offline bundles of installers
+	Bundle {
+		#[clap(subcommand)]
+		command: BundleCommand,
+	},
+	/// Manages the cache of downloaded installers
+	Cache {
+		#[clap(subcommand)]
+		command: CacheCommand,
+	},
+	/// Creates keys for and signs team manifests
+	Manifest {
+		#[clap(subcommand)]
+		command: ManifestCommand,
+	},
+	/// Checks the network connection
+	Net {
+		#[clap(subcommand)]
+		command: NetCommand,
+	},
+	/// Shares the cache of downloaded installers with other computers over HTTP
+	Serve {
+		/// The address and port to listen on
+		#[arg(long, default_value = "0.0.0.0:8080")]
+		address: String,
+	},
+	/// Uninstalls a package
+	Uninstall {
+		/// The names of the packages to uninstall
+		packages: Vec<String>,
+	},
 	/// Launches a package
 	Launch {
 		/// The names of the packages to launch
-		packages: Vec<Package>,
+		packages: Vec<String>,
+	},
+	/// Shows the packages that have been installed on this computer
+	Status {
+		/// Look for packages inside of this directory instead of the real filesystem
+		#[arg(long, hide = true)]
+		root: Option<PathBuf>,
+	},
+	/// Lists installed packages that have a newer release available
+	Outdated,
+	/// Reinstalls installed packages that have a newer release available
+	Update,
+}
+
+impl Subcommand {
+	/// Check if the command uses the package registry, which needs the team manifest
+	fn uses_packages(&self) -> bool {
+		!matches!(
+			self,
+			Self::Cache { .. } | Self::Manifest { .. } | Self::Serve { .. }
+		)
+	}
+
+	/// Check if the command has to use the cached team manifest instead of fetching it.
+	/// Installing from a bundle must work offline, and checking the network should not
+	/// depend on it
+	fn uses_cached_manifest(&self) -> bool {
+		matches!(
+			self,
+			Self::Install {
+				from_bundle: Some(..),
+				..
+			} | Self::Net { .. }
+		)
+	}
+}
+
+#[derive(clap::Subcommand)]
+enum BundleCommand {
+	/// Downloads the installers for packages into a directory that can be installed from offline
+	Export {
+		/// The directory to export the bundle to
+		dir: PathBuf,
+		/// The names of the packages to bundle
+		packages: Vec<String>,
+		/// The name of a profile to bundle the packages of
+		#[arg(long)]
+		profile: Option<String>,
+	},
+}
+
+#[derive(clap::Subcommand)]
+enum ManifestCommand {
+	/// Generates a key pair for signing team manifests
+	Keygen {
+		/// The file to save the private key to
+		key: PathBuf,
+	},
+	/// Sets the publish time of a team manifest to now and signs it
+	Sign {
+		/// The team manifest to sign
+		manifest: PathBuf,
+		/// The file with the private key from keygen
+		#[arg(long)]
+		key: PathBuf,
+	},
+}
+
+#[derive(clap::Subcommand)]
+enum NetCommand {
+	/// Checks which of the servers that the tool downloads from can be reached
+	Check,
+}
+
+#[derive(clap::Subcommand)]
+enum CacheCommand {
+	/// Lists the cached downloads, most recently used first
+	List,
+	/// Shows the disk space used by the cache
+	Size,
+	/// Removes all but the most recently used downloads
+	Prune {
+		/// The number of downloads to keep
+		#[arg(long)]
+		keep: usize,
 	},
+	/// Removes all cached downloads
+	Clear,
 }
@@ -0,0 +1,851 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::archive::ArchiveFormat;
+use crate::assets::DEFAULT_MANIFEST;
+use crate::dependencies::topological_sort;
+use crate::net::NetworkOverrides;
+use crate::output::OutputTrait;
+use crate::probe::VersionProbe;
+use crate::season::Season;
+use crate::select::{AssetRules, Selection};
+use crate::utils::{
+	download_bytes, download_github_release, get_github_releases, GithubRelease,
+	GITHUB_RELEASES_PER_PAGE,
+};
+
+/// Name of the override manifest in the data directory
+pub const OVERRIDE_MANIFEST_FILE: &str = "packages.json";
+
+/// Most pages of Github releases to search through. Older releases than this are never used
+const MAX_RELEASE_PAGES: u32 = 5;
+
+/// Server that files shared from Google Drive are downloaded from
+const GOOGLE_DRIVE_DOWNLOAD_URL: &str = "https://drive.usercontent.google.com";
+
+/// List of package definitions that make up the package registry
+#[derive(Deserialize)]
+pub struct Manifest {
+	/// When a team manifest was signed. Team manifests that are older than the last one that was
+	/// used are rejected, so that an old manifest can't be served again
+	#[serde(default)]
+	pub published: Option<DateTime<Utc>>,
+	pub packages: Vec<PackageDef>,
+	/// IDs of the packages that install-all installs, with the more important ones first
+	#[serde(default)]
+	pub default_packages: Vec<String>,
+	/// Install profiles, mapping a profile name to the IDs of the packages in it
+	#[serde(default)]
+	pub profiles: BTreeMap<String, Vec<String>>,
+}
+
+/// Definition of a single package
+#[derive(Deserialize)]
+pub struct PackageDef {
+	/// Unique ID used on the command line and in saved data
+	pub id: String,
+	pub display_name: String,
+	pub short_description: String,
+	/// Name of a built-in icon
+	#[serde(default)]
+	pub icon: Option<String>,
+	/// ID of the package that this package is installed with. The parent is also a dependency
+	#[serde(default)]
+	pub parent: Option<String>,
+	/// IDs of other packages that need to be installed for this package to work
+	#[serde(default)]
+	pub dependencies: Vec<String>,
+	/// Whether each season has its own install of the package that can exist alongside the others
+	#[serde(default)]
+	pub seasonal: bool,
+	#[serde(default)]
+	pub install: Option<InstallSpec>,
+	#[serde(default)]
+	pub launch: Option<LaunchTarget>,
+	/// How to find the version of the package that is installed
+	#[serde(default)]
+	pub version_probe: Option<VersionProbe>,
+	/// How to remove the package. Files downloaded by the tool are always removed
+	#[serde(default)]
+	pub uninstall: Option<UninstallMethod>,
+	/// Changes to the network policy for downloads of the package
+	#[serde(default)]
+	pub network: Option<NetworkOverrides>,
+}
+
+impl PackageDef {
+	/// Get the IDs of all of the dependencies of this package, including its parent
+	pub fn all_dependencies(&self) -> Vec<&str> {
+		self.parent
+			.iter()
+			.chain(self.dependencies.iter())
+			.map(String::as_str)
+			.collect()
+	}
+}
+
+/// How a package is installed
+#[derive(Deserialize)]
+pub struct InstallSpec {
+	/// Where the file to install comes from
+	pub source: InstallSource,
+	/// What is done with the file to install the package
+	pub method: InstallMethod,
+	/// What the installer program is built with, which decides how it is run silently.
+	/// Packages without one always install interactively
+	#[serde(default)]
+	pub technology: Option<InstallerTechnology>,
+	/// Directory to install the package to. Can contain path placeholders
+	#[serde(default)]
+	pub install_dir: Option<String>,
+	/// Extra arguments for silent installs, like options that skip optional components.
+	/// These are passed to the installer as-is
+	#[serde(default)]
+	pub silent_args: Vec<String>,
+	/// Instruction shown to the user when the installer asks for input, like which options to pick
+	#[serde(default)]
+	pub instruction: Option<String>,
+	/// Configuration built into the tool that is applied after the package is installed
+	#[serde(default)]
+	pub configure: Option<Configuration>,
+}
+
+impl InstallSpec {
+	/// Get the name that the file to install is saved with
+	pub fn get_file_name(&self) -> String {
+		let extension = match &self.method {
+			InstallMethod::ExtractArchive { format, .. } => format.get_extension(),
+			InstallMethod::ExtractIso { .. } => "iso",
+			_ if self.technology == Some(InstallerTechnology::Msi) => "msi",
+			_ => "exe",
+		};
+		format!("installer.{extension}")
+	}
+}
+
+/// How the file from an install source is installed
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InstallMethod {
+	/// Run the file as an installer program and wait for it to finish
+	Run,
+	/// Run the file as an installer program with administrator permissions, wait for it to
+	/// finish and check its exit code
+	RunElevated,
+	/// Extract the file as an archive
+	ExtractArchive {
+		format: ArchiveFormat,
+		/// Directory to extract to, which is replaced. Can contain path placeholders
+		#[serde(default = "default_extract_dir")]
+		dir: String,
+		/// Whether to leave out the directory that everything in the archive is in, if there is one
+		#[serde(default)]
+		strip_top_level: bool,
+	},
+	/// Extract the file as a disc image and run an installer program from it. The extracted
+	/// files are deleted once the installer finishes
+	ExtractIso {
+		/// Path of the installer program inside of the image
+		program: String,
+	},
+}
+
+fn default_extract_dir() -> String {
+	"{package_data}/extracted".into()
+}
+
+/// Configuration routines built into the tool
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Configuration {
+	/// Team preferences and joystick configs for AdvantageScope
+	#[serde(rename = "advantagescope")]
+	AdvantageScope,
+}
+
+/// Tools that installer programs are built with
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallerTechnology {
+	/// Nullsoft Scriptable Install System, used by most Electron apps
+	Nsis,
+	/// Inno Setup
+	Inno,
+	/// Windows Installer packages, which are run with msiexec
+	Msi,
+	/// Squirrel.Windows, which always installs to the local app data directory
+	Squirrel,
+	/// Installers that use NI Package Manager
+	NiPackageManager,
+}
+
+impl InstallerTechnology {
+	/// Get the switches that make the installer run without any prompts
+	pub fn get_silent_args(&self) -> &'static [&'static str] {
+		match self {
+			Self::Nsis => &["/S"],
+			Self::Inno => &["/VERYSILENT", "/SUPPRESSMSGBOXES", "/NORESTART", "/SP-"],
+			Self::Msi => &["/qn", "/norestart"],
+			Self::Squirrel => &["--silent"],
+			Self::NiPackageManager => &["--quiet", "--accept-eulas", "--prevent-reboot"],
+		}
+	}
+
+	/// Interpret the exit code of an installer
+	pub fn get_exit(&self, code: i32) -> InstallerExit {
+		match (self, code) {
+			(_, 0) => InstallerExit::Success,
+			(Self::Nsis, 1) => InstallerExit::Cancelled,
+			// 2 is a cancel before installing and 5 is a cancel during it
+			(Self::Inno, 2 | 5) => InstallerExit::Cancelled,
+			(Self::Msi, 1602) => InstallerExit::Cancelled,
+			(Self::Msi, 1641 | 3010) => InstallerExit::RebootRequired,
+			(Self::NiPackageManager, -125071) => InstallerExit::RebootRequired,
+			_ => InstallerExit::Failed,
+		}
+	}
+
+	/// Get the argument that sets the install directory, or None if the installer can't change it
+	pub fn get_install_dir_arg(&self, dir: &Path) -> Option<String> {
+		let dir = dir.display();
+		match self {
+			// NSIS reads the rest of the command line as the directory, so it is not quoted
+			Self::Nsis => Some(format!("/D={dir}")),
+			Self::Inno => Some(format!("/DIR=\"{dir}\"")),
+			Self::Msi => Some(format!("INSTALLDIR=\"{dir}\"")),
+			Self::Squirrel | Self::NiPackageManager => None,
+		}
+	}
+}
+
+/// What the exit code of an installer means
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InstallerExit {
+	Success,
+	/// The package was installed, but the computer has to restart before it can be used
+	RebootRequired,
+	/// The user cancelled the installer
+	Cancelled,
+	Failed,
+}
+
+impl InstallerExit {
+	/// Interpret the exit code of an installer whose technology is not known
+	pub fn from_code(code: i32) -> Self {
+		match code {
+			0 => Self::Success,
+			_ => Self::Failed,
+		}
+	}
+}
+
+/// Location of the file that an installer downloads
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InstallSource {
+	/// A file at a fixed URL
+	Url {
+		url: String,
+		/// SHA-256 checksum of the file in hex
+		#[serde(default)]
+		sha256: Option<String>,
+	},
+	/// A file at a different URL for each season, keyed by the season year
+	SeasonUrl {
+		urls: BTreeMap<String, String>,
+		/// SHA-256 checksums of the files in hex, keyed by the season year
+		#[serde(default)]
+		sha256: BTreeMap<String, String>,
+	},
+	/// An asset of the latest release of a Github repository. Prereleases, drafts and older
+	/// releases are searched if the selection rules allow them
+	GithubLatest {
+		user: String,
+		repo: String,
+		/// Patterns that the asset name must all contain
+		#[serde(default)]
+		asset: Vec<String>,
+		/// Rules for choosing the release and asset
+		#[serde(default)]
+		select: AssetRules,
+		/// Patterns for a checksum file in the release, used if Github does not have a digest for the asset
+		#[serde(default)]
+		checksum_asset: Option<Vec<String>>,
+	},
+	/// An asset of the newest Github release whose tag contains a pattern
+	GithubRelease {
+		user: String,
+		repo: String,
+		tag: String,
+		/// Patterns that the asset name must all contain
+		#[serde(default)]
+		asset: Vec<String>,
+		/// Rules for choosing the release and asset
+		#[serde(default)]
+		select: AssetRules,
+		/// Patterns for a checksum file in the release, used if Github does not have a digest for the asset
+		#[serde(default)]
+		checksum_asset: Option<Vec<String>>,
+	},
+	/// A file shared publicly from Google Drive
+	GoogleDrive {
+		/// ID of the file, from its share link
+		id: String,
+		/// SHA-256 checksum of the file in hex
+		#[serde(default)]
+		sha256: Option<String>,
+	},
+}
+
+/// The file that an install source points to
+pub struct ResolvedSource {
+	pub url: String,
+	/// File name of the asset
+	pub asset: String,
+	/// The release tag, if the source has one
+	pub version: Option<String>,
+	/// Expected SHA-256 checksum of the file in hex, if it is known
+	pub sha256: Option<String>,
+	/// How the asset was chosen from a Github release, if it came from one
+	pub selection: Option<Selection>,
+}
+
+impl ResolvedSource {
+	/// Create a source for a plain URL, using the last part of the URL path as the asset name
+	fn from_url(url: String, version: Option<String>, sha256: Option<String>) -> Self {
+		let path = url.split(['?', '#']).next().unwrap_or_default();
+		let asset = path.rsplit('/').next().unwrap_or_default().to_string();
+		Self {
+			url,
+			asset,
+			version,
+			sha256,
+			selection: None,
+		}
+	}
+
+	/// Create a source for the asset chosen from a Github release. The checksum comes from the digest
+	/// that Github publishes for the asset, or from a checksum file in the release if there are patterns for one
+	async fn from_github(
+		client: &Client,
+		release: &GithubRelease,
+		selection: Selection,
+		checksum_asset: Option<&[String]>,
+		out: &mut impl OutputTrait,
+	) -> anyhow::Result<Self> {
+		let asset = selection
+			.get_asset(release)
+			.ok_or_else(|| anyhow!("No valid asset file found\n{selection}"))?;
+		let mut sha256 = asset.get_sha256();
+		if let (None, Some(checksum_asset)) = (&sha256, checksum_asset) {
+			let checksums = release
+				.get_asset_patterns(&patterns(checksum_asset))
+				.ok_or(anyhow!("No checksum file found in release"))?;
+			let contents = download_bytes(client, &checksums.browser_download_url, out)
+				.await
+				.context("Failed to download checksum file")?;
+			let contents = String::from_utf8_lossy(&contents);
+			sha256 = Some(
+				parse_checksum_file(&contents, &asset.name)
+					.ok_or(anyhow!("Checksum file does not list {}", asset.name))?,
+			);
+		}
+
+		Ok(Self {
+			url: asset.browser_download_url.clone(),
+			asset: asset.name.clone(),
+			version: Some(release.tag_name.clone()),
+			sha256,
+			selection: Some(selection),
+		})
+	}
+}
+
+/// Find the checksum of a file in a checksum file. Supports the `sha256sum` format of
+/// `<hash>  <file name>` lines, as well as files with only a single hash in them
+fn parse_checksum_file(contents: &str, file_name: &str) -> Option<String> {
+	let is_hash = |x: &str| x.len() == 64 && x.chars().all(|c| c.is_ascii_hexdigit());
+	let lines: Vec<_> = contents
+		.lines()
+		.map(str::trim)
+		.filter(|x| !x.is_empty())
+		.collect();
+	for line in &lines {
+		let mut parts = line.split_whitespace();
+		let (Some(hash), Some(name)) = (parts.next(), parts.next()) else {
+			continue;
+		};
+		// Binary mode files are marked with a star
+		if is_hash(hash) && name.trim_start_matches('*') == file_name {
+			return Some(hash.to_lowercase());
+		}
+	}
+
+	// A file with a single bare hash is only for one file
+	match lines[..] {
+		[hash] if is_hash(hash) => Some(hash.to_lowercase()),
+		_ => None,
+	}
+}
+
+impl InstallSource {
+	/// Get the server that the source downloads from in a season, like `https://api.github.com`
+	pub fn get_endpoint(&self, season: Season) -> Option<String> {
+		let url = match self {
+			Self::Url { url, .. } => season.expand(url),
+			Self::SeasonUrl { urls, .. } => urls.get(&season.to_string())?.clone(),
+			Self::GithubLatest { .. } | Self::GithubRelease { .. } => {
+				return Some("https://api.github.com".into())
+			}
+			Self::GoogleDrive { .. } => return Some(GOOGLE_DRIVE_DOWNLOAD_URL.into()),
+		};
+		let url = reqwest::Url::parse(&url).ok()?;
+		Some(url.origin().ascii_serialization())
+	}
+
+	/// Resolve the file to download for a season
+	pub async fn resolve(
+		&self,
+		client: &Client,
+		season: Season,
+		out: &mut impl OutputTrait,
+	) -> anyhow::Result<ResolvedSource> {
+		match self {
+			Self::Url { url, sha256 } => Ok(ResolvedSource::from_url(
+				season.expand(url),
+				None,
+				sha256.clone(),
+			)),
+			Self::SeasonUrl { urls, sha256 } => {
+				let season_key = season.to_string();
+				let url = urls
+					.get(&season_key)
+					.ok_or(anyhow!("No download is available for season {season}"))?;
+				// The season is not a version that can be compared with what the version probe of the
+				// package finds, so these files are treated as unversioned
+				Ok(ResolvedSource::from_url(
+					url.clone(),
+					None,
+					sha256.get(&season_key).cloned(),
+				))
+			}
+			Self::GithubLatest {
+				user,
+				repo,
+				asset,
+				select,
+				checksum_asset,
+			} => {
+				let (release, selection) = if select.needs_release_list() {
+					find_github_release(client, user, repo, None, asset, select, out)
+						.await?
+						.ok_or(anyhow!("No valid release found"))?
+				} else {
+					let release = download_github_release(client, user, repo, out)
+						.await
+						.context("Failed to get Github release")?;
+					let selection = select.select(&release, asset);
+					(release, selection)
+				};
+				ResolvedSource::from_github(
+					client,
+					&release,
+					selection,
+					checksum_asset.as_deref(),
+					out,
+				)
+				.await
+			}
+			Self::GithubRelease {
+				user,
+				repo,
+				tag,
+				asset,
+				select,
+				checksum_asset,
+			} => {
+				let tag = season.expand(tag);
+				let (release, selection) =
+					find_github_release(client, user, repo, Some(&tag), asset, select, out)
+						.await?
+						.ok_or(anyhow!("No valid release found for season {season}"))?;
+				ResolvedSource::from_github(
+					client,
+					&release,
+					selection,
+					checksum_asset.as_deref(),
+					out,
+				)
+				.await
+			}
+			Self::GoogleDrive { id, sha256 } => Ok(ResolvedSource {
+				// Confirming skips the page that Drive shows for files too large to virus scan
+				url: format!(
+					"{GOOGLE_DRIVE_DOWNLOAD_URL}/download?id={id}&export=download&confirm=t"
+				),
+				asset: id.clone(),
+				version: None,
+				sha256: sha256.clone(),
+				selection: None,
+			}),
+		}
+	}
+}
+
+/// Search the releases of a Github repository from newest to oldest for the first one whose tag
+/// contains a pattern and that has an asset meeting the rules
+async fn find_github_release(
+	client: &Client,
+	user: &str,
+	repo: &str,
+	tag: Option<&str>,
+	patterns: &[String],
+	rules: &AssetRules,
+	out: &mut impl OutputTrait,
+) -> anyhow::Result<Option<(GithubRelease, Selection)>> {
+	for page in 1..=MAX_RELEASE_PAGES {
+		let releases = get_github_releases(client, user, repo, page, out)
+			.await
+			.context("Failed to get Github releases")?;
+		let count = releases.len();
+		for release in releases {
+			if tag.is_some_and(|x| !release.tag_name.contains(x))
+				|| rules.check_release(&release).is_err()
+			{
+				continue;
+			}
+			let selection = rules.select(&release, patterns);
+			if selection.chosen.is_some() {
+				return Ok(Some((release, selection)));
+			}
+		}
+
+		if count < GITHUB_RELEASES_PER_PAGE {
+			break;
+		}
+	}
+
+	Ok(None)
+}
+
+fn patterns(pats: &[String]) -> Vec<&str> {
+	pats.iter().map(String::as_str).collect()
+}
+
+/// How a package is launched. Paths are resolved using a PathContext and URLs can contain
+/// season placeholders
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LaunchTarget {
+	/// Run an executable
+	Program {
+		path: String,
+		/// Whether the program needs administrator permissions
+		#[serde(default)]
+		elevated: bool,
+		/// Whether to run the program from the directory it is in
+		#[serde(default)]
+		in_directory: bool,
+	},
+	/// Run a Windows script using cscript
+	Script { path: String },
+	/// Open a URL in the default browser
+	Url { url: String },
+}
+
+/// How a package is uninstalled. Paths are resolved using a PathContext
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UninstallMethod {
+	/// Run the uninstaller registered with Windows for a program with a display name. A version
+	/// number after the name is allowed, since many installers add one
+	Registered { display_name: String },
+	/// Run an uninstaller program
+	Program { path: String },
+	/// Delete the directory that the package is in
+	Delete { path: String },
+}
+
+impl Manifest {
+	/// Load a base manifest, or the embedded manifest if there is none, with the override
+	/// manifest in the data directory applied on top
+	pub fn load(data_dir: &Path, base: Option<&str>) -> anyhow::Result<Self> {
+		let mut manifest: Value = serde_json::from_str(base.unwrap_or(DEFAULT_MANIFEST))
+			.context("Failed to parse base manifest")?;
+
+		let override_path = data_dir.join(OVERRIDE_MANIFEST_FILE);
+		if override_path.exists() {
+			let contents = std::fs::read_to_string(&override_path)
+				.context("Failed to read override manifest")?;
+			let overrides: Value =
+				serde_json::from_str(&contents).context("Failed to parse override manifest")?;
+			apply_overrides(&mut manifest, overrides)
+				.context("Failed to apply override manifest")?;
+		}
+
+		Self::from_value(manifest)
+	}
+
+	/// Parse and validate a manifest from JSON
+	pub fn from_value(value: Value) -> anyhow::Result<Self> {
+		let out: Self = serde_json::from_value(value).context("Invalid package manifest")?;
+		out.validate()?;
+		Ok(out)
+	}
+
+	/// Check that package IDs are unique, that parents and dependencies exist,
+	/// and that there are no dependency cycles
+	fn validate(&self) -> anyhow::Result<()> {
+		let mut ids = HashSet::new();
+		for pkg in &self.packages {
+			if !ids.insert(pkg.id.as_str()) {
+				bail!("Duplicate package {}", pkg.id);
+			}
+		}
+		for pkg in &self.packages {
+			if let Some(parent) = &pkg.parent {
+				if !ids.contains(parent.as_str()) {
+					bail!("Package {} has unknown parent {parent}", pkg.id);
+				}
+			}
+			for dep in &pkg.dependencies {
+				if !ids.contains(dep.as_str()) {
+					bail!("Package {} has unknown dependency {dep}", pkg.id);
+				}
+			}
+		}
+		for id in &self.default_packages {
+			if !ids.contains(id.as_str()) {
+				bail!("Unknown default package {id}");
+			}
+		}
+		for (name, packages) in &self.profiles {
+			for id in packages {
+				if !ids.contains(id.as_str()) {
+					bail!("Profile {name} has unknown package {id}");
+				}
+			}
+		}
+
+		let roots: Vec<_> = self.packages.iter().map(|x| x.id.as_str()).collect();
+		topological_sort(&roots, |id| {
+			self.packages
+				.iter()
+				.find(|x| x.id == id)
+				.map(PackageDef::all_dependencies)
+				.unwrap_or_default()
+		})?;
+
+		Ok(())
+	}
+}
+
+/// Merge override package entries into a manifest. Entries with an existing ID replace the fields
+/// they specify, and entries with a new ID are added to the end
+fn apply_overrides(manifest: &mut Value, overrides: Value) -> anyhow::Result<()> {
+	// Fields other than the package list replace the ones in the manifest
+	if let (Value::Object(manifest), Value::Object(overrides)) = (&mut *manifest, &overrides) {
+		for (key, value) in overrides {
+			if key != "packages" {
+				manifest.insert(key.clone(), value.clone());
+			}
+		}
+	}
+
+	let packages = manifest
+		.get_mut("packages")
+		.and_then(Value::as_array_mut)
+		.ok_or(anyhow!("Manifest is missing a package list"))?;
+	let Some(Value::Array(overrides)) = overrides.get("packages").cloned() else {
+		bail!("Override manifest is missing a package list");
+	};
+
+	for over in overrides {
+		let Value::Object(over) = over else {
+			bail!("Override package entry is not an object");
+		};
+		let id = over
+			.get("id")
+			.and_then(Value::as_str)
+			.ok_or(anyhow!("Override package entry is missing an ID"))?
+			.to_owned();
+
+		let existing = packages
+			.iter_mut()
+			.find(|x| x.get("id").and_then(Value::as_str) == Some(id.as_str()));
+		match existing {
+			Some(Value::Object(existing)) => existing.extend(over),
+			_ => packages.push(Value::Object(over)),
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use serde_json::json;
+	use tiny_http::{Response, Server};
+
+	use super::*;
+	use crate::output::NullOutput;
+
+	fn get_source(value: Value) -> InstallSource {
+		serde_json::from_value(value).unwrap()
+	}
+
+	fn get_season(season: &str) -> Season {
+		Season::from_str(season).unwrap()
+	}
+
+	async fn resolve(source: &InstallSource, season: &str) -> anyhow::Result<ResolvedSource> {
+		source
+			.resolve(&Client::new(), get_season(season), &mut NullOutput)
+			.await
+	}
+
+	/// A release with an installer for each architecture and the files that are published next to them
+	fn get_release(checksums_url: &str) -> GithubRelease {
+		serde_json::from_value(json!({
+			"tag_name": "v2024.2.1",
+			"assets": [
+				{
+					"name": "App-Setup-2024.2.1-arm64.exe",
+					"url": "",
+					"browser_download_url": "https://example.com/arm64.exe",
+					"digest": "sha256:1111"
+				},
+				{
+					"name": "App-Setup-2024.2.1-x64.exe.blockmap",
+					"url": "",
+					"browser_download_url": "https://example.com/x64.exe.blockmap"
+				},
+				{
+					"name": "App-Setup-2024.2.1-x64.exe",
+					"url": "",
+					"browser_download_url": "https://example.com/x64.exe",
+					"digest": "sha256:ABCD"
+				},
+				{
+					"name": "App-Setup-2024.2.1-x64.zip",
+					"url": "",
+					"browser_download_url": "https://example.com/x64.zip"
+				},
+				{
+					"name": "checksums.txt",
+					"url": "",
+					"browser_download_url": checksums_url
+				}
+			]
+		}))
+		.unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_resolve_url() {
+		let source = get_source(json!({
+			"type": "url",
+			"url": "https://example.com/{season}/Tool.zip?raw=1",
+			"sha256": "abcd"
+		}));
+		let resolved = resolve(&source, "2024").await.unwrap();
+		assert_eq!(resolved.url, "https://example.com/2024/Tool.zip?raw=1");
+		assert_eq!(resolved.asset, "Tool.zip");
+		assert_eq!(resolved.version, None);
+		assert_eq!(resolved.sha256.as_deref(), Some("abcd"));
+	}
+
+	#[tokio::test]
+	async fn test_resolve_season_url() {
+		let source = get_source(json!({
+			"type": "season_url",
+			"urls": {
+				"2024": "https://example.com/tools-2024.iso",
+				"2025": "https://example.com/tools-2025.iso"
+			},
+			"sha256": { "2024": "abcd" }
+		}));
+
+		let resolved = resolve(&source, "2024").await.unwrap();
+		assert_eq!(resolved.url, "https://example.com/tools-2024.iso");
+		assert_eq!(resolved.asset, "tools-2024.iso");
+		assert_eq!(resolved.version, None);
+		assert_eq!(resolved.sha256.as_deref(), Some("abcd"));
+
+		let resolved = resolve(&source, "2025").await.unwrap();
+		assert_eq!(resolved.asset, "tools-2025.iso");
+		assert_eq!(resolved.sha256, None);
+
+		assert!(resolve(&source, "2023").await.is_err());
+	}
+
+	#[tokio::test]
+	async fn test_resolve_github_asset() {
+		let release = get_release("https://example.com/checksums.txt");
+		let rules: AssetRules = serde_json::from_value(json!({
+			"arch": "x64",
+			"extension": [".exe", ".zip"]
+		}))
+		.unwrap();
+		let selection = rules.select(&release, &["Setup".into()]);
+		let resolved =
+			ResolvedSource::from_github(&Client::new(), &release, selection, None, &mut NullOutput)
+				.await
+				.unwrap();
+
+		assert_eq!(resolved.url, "https://example.com/x64.exe");
+		assert_eq!(resolved.asset, "App-Setup-2024.2.1-x64.exe");
+		assert_eq!(resolved.version.as_deref(), Some("v2024.2.1"));
+		assert_eq!(resolved.sha256.as_deref(), Some("abcd"));
+	}
+
+	#[tokio::test]
+	async fn test_resolve_github_no_asset() {
+		let release = get_release("https://example.com/checksums.txt");
+		let selection = AssetRules::default().select(&release, &["linux".into()]);
+		let result =
+			ResolvedSource::from_github(&Client::new(), &release, selection, None, &mut NullOutput)
+				.await;
+		assert!(result.is_err());
+	}
+
+	#[tokio::test]
+	async fn test_resolve_github_checksum_file() {
+		let server = Server::http("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/checksums.txt", server.server_addr());
+		let hash = "e".repeat(64);
+		let contents = format!(
+			"{}  App-Setup-2024.2.1-arm64.exe\n{hash}  App-Setup-2024.2.1-x64.zip\n",
+			"f".repeat(64)
+		);
+		std::thread::spawn(move || {
+			if let Ok(request) = server.recv() {
+				let _ = request.respond(Response::from_string(contents));
+			}
+		});
+
+		let release = get_release(&url);
+		let rules: AssetRules = serde_json::from_value(json!({ "extension": [".zip"] })).unwrap();
+		let selection = rules.select(&release, &[]);
+		let checksum_asset = ["checksums".to_string()];
+		let resolved = ResolvedSource::from_github(
+			&Client::new(),
+			&release,
+			selection,
+			Some(&checksum_asset),
+			&mut NullOutput,
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(resolved.asset, "App-Setup-2024.2.1-x64.zip");
+		assert_eq!(resolved.sha256, Some(hash));
+	}
+}
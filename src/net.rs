@@ -0,0 +1,270 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::output::OutputTrait;
+
+/// Longest time to wait between retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// The network policy from the config
+static POLICY: RwLock<NetworkPolicy> = RwLock::new(NetworkPolicy::DEFAULT);
+
+tokio::task_local! {
+	/// Overrides of the network policy for the package that is being worked on
+	static OVERRIDES: NetworkOverrides;
+}
+
+/// Timeouts and retries for network requests
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NetworkPolicy {
+	/// Seconds to wait for a connection to a server
+	pub connect_timeout_secs: u64,
+	/// Seconds to wait for a response or for the next part of a download
+	pub timeout_secs: u64,
+	/// How many times to retry a request that failed because of a server or connection problem
+	pub retries: u32,
+	/// Milliseconds to wait before the first retry. The delay doubles with each retry
+	pub retry_delay_ms: u64,
+}
+
+impl NetworkPolicy {
+	const DEFAULT: Self = Self {
+		connect_timeout_secs: 15,
+		timeout_secs: 30,
+		retries: 3,
+		retry_delay_ms: 1000,
+	};
+
+	/// Get the policy with overrides applied
+	fn with_overrides(mut self, overrides: &NetworkOverrides) -> Self {
+		if let Some(timeout) = overrides.timeout_secs {
+			self.timeout_secs = timeout;
+		}
+		if let Some(retries) = overrides.retries {
+			self.retries = retries;
+		}
+		if let Some(delay) = overrides.retry_delay_ms {
+			self.retry_delay_ms = delay;
+		}
+		self
+	}
+
+	pub fn get_connect_timeout(&self) -> Duration {
+		Duration::from_secs(self.connect_timeout_secs)
+	}
+
+	pub fn get_timeout(&self) -> Duration {
+		Duration::from_secs(self.timeout_secs)
+	}
+
+	/// Check if a failed request should be tried again
+	pub fn should_retry(&self, error: &anyhow::Error, attempt: u32) -> bool {
+		attempt < self.retries && is_transient(error)
+	}
+
+	/// Wait before retrying a failed request, using exponential backoff with jitter so that
+	/// many computers don't retry at the same time
+	pub async fn wait_to_retry(
+		&self,
+		error: &anyhow::Error,
+		attempt: u32,
+		out: &mut impl OutputTrait,
+	) {
+		let delay = Duration::from_millis(self.retry_delay_ms)
+			.saturating_mul(2u32.saturating_pow(attempt))
+			.min(MAX_RETRY_DELAY);
+		// Wait somewhere between half and all of the delay
+		let jitter = RandomState::new().build_hasher().finish() % 1000;
+		let delay = delay / 2 + delay.mul_f64(jitter as f64 / 2000.0);
+
+		out.progress(format!(
+			"Request failed: {error:#}. Retrying in {:.1} seconds ({}/{})",
+			delay.as_secs_f64(),
+			attempt + 1,
+			self.retries
+		));
+		tokio::time::sleep(delay).await;
+	}
+}
+
+impl Default for NetworkPolicy {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+/// Changes to the network policy for a single package, such as a longer timeout for a slow server.
+/// The connect timeout is shared by all packages
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct NetworkOverrides {
+	pub timeout_secs: Option<u64>,
+	pub retries: Option<u32>,
+	pub retry_delay_ms: Option<u64>,
+}
+
+/// Proxies to send requests through. Without any, the proxies of the system are used
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ProxyConfig {
+	/// Proxy for HTTP requests, like `http://proxy.school.org:8080`
+	pub http: Option<String>,
+	/// Proxy for HTTPS requests
+	pub https: Option<String>,
+	/// Hosts and IP ranges that are connected to directly, like `.school.org` or `10.0.0.0/8`
+	pub no_proxy: Vec<String>,
+}
+
+/// Create the HTTP client with the proxies, trusted certificates and connect timeout from the config
+pub fn create_client(config: &Config) -> anyhow::Result<Client> {
+	let mut builder = Client::builder().connect_timeout(config.network.get_connect_timeout());
+
+	let no_proxy = NoProxy::from_string(&config.proxy.no_proxy.join(","));
+	if let Some(url) = &config.proxy.http {
+		let proxy = Proxy::http(url).with_context(|| format!("Invalid HTTP proxy {url}"))?;
+		builder = builder.proxy(proxy.no_proxy(no_proxy.clone()));
+	}
+	if let Some(url) = &config.proxy.https {
+		let proxy = Proxy::https(url).with_context(|| format!("Invalid HTTPS proxy {url}"))?;
+		builder = builder.proxy(proxy.no_proxy(no_proxy));
+	}
+
+	// Networks that inspect HTTPS traffic sign it with their own root certificate
+	for path in &config.ca_bundles {
+		let pem = std::fs::read(path)
+			.with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+		let cert = Certificate::from_pem(&pem)
+			.with_context(|| format!("Invalid CA bundle {}", path.display()))?;
+		builder = builder.add_root_certificate(cert);
+	}
+
+	builder.build().context("Failed to create HTTP client")
+}
+
+/// Set the network policy from the config
+pub fn set_network_policy(policy: NetworkPolicy) {
+	*POLICY.write().unwrap_or_else(PoisonError::into_inner) = policy;
+}
+
+/// Get the network policy to use, including the overrides of the current package
+pub fn get_network_policy() -> NetworkPolicy {
+	let policy = POLICY
+		.read()
+		.unwrap_or_else(PoisonError::into_inner)
+		.clone();
+	OVERRIDES
+		.try_with(|x| policy.clone().with_overrides(x))
+		.unwrap_or(policy)
+}
+
+/// Run a future with the network policy overrides of a package
+pub async fn with_network_overrides<F: Future>(
+	overrides: Option<&NetworkOverrides>,
+	f: F,
+) -> F::Output {
+	OVERRIDES
+		.scope(overrides.cloned().unwrap_or_default(), f)
+		.await
+}
+
+/// Run a request, retrying it according to the network policy if it fails because of a
+/// server or connection problem
+pub async fn retry<T, F, Fut>(out: &mut impl OutputTrait, mut f: F) -> anyhow::Result<T>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = anyhow::Result<T>>,
+{
+	let policy = get_network_policy();
+	let mut attempt = 0;
+	loop {
+		match f().await {
+			Err(e) if policy.should_retry(&e, attempt) => {
+				policy.wait_to_retry(&e, attempt, out).await;
+				attempt += 1;
+			}
+			result => return result,
+		}
+	}
+}
+
+/// Wait for a future with the timeout of the network policy
+pub async fn with_timeout<T>(
+	policy: &NetworkPolicy,
+	f: impl Future<Output = T>,
+) -> std::io::Result<T> {
+	tokio::time::timeout(policy.get_timeout(), f)
+		.await
+		.map_err(|_| std::io::Error::new(ErrorKind::TimedOut, "The server stopped responding"))
+}
+
+/// Check if an error is caused by a server or connection problem that might go away if the
+/// request is tried again
+fn is_transient(error: &anyhow::Error) -> bool {
+	error.chain().any(|cause| {
+		if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+			if let Some(status) = e.status() {
+				return status.is_server_error()
+					|| status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+			}
+			return e.is_timeout() || e.is_connect() || e.is_request() || e.is_body();
+		}
+		if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+			return matches!(
+				e.kind(),
+				ErrorKind::TimedOut
+					| ErrorKind::ConnectionReset
+					| ErrorKind::ConnectionAborted
+					| ErrorKind::BrokenPipe
+					| ErrorKind::UnexpectedEof
+			);
+		}
+		false
+	})
+}
+
+/// Whether a server could be reached
+pub struct EndpointStatus {
+	pub url: String,
+	/// The status code and how long the server took to respond, or why it could not be reached
+	pub result: anyhow::Result<(reqwest::StatusCode, Duration)>,
+}
+
+impl EndpointStatus {
+	/// Check if the failure looks like a certificate that is not trusted
+	pub fn is_certificate_error(&self) -> bool {
+		let Err(e) = &self.result else {
+			return false;
+		};
+		let message = format!("{e:#}").to_lowercase();
+		message.contains("certificate") || message.contains("unknownissuer")
+	}
+}
+
+/// Check if servers can be reached with the client. Any response counts, even an error status
+pub async fn check_endpoints(client: &Client, urls: Vec<String>) -> Vec<EndpointStatus> {
+	let timeout = get_network_policy().get_timeout();
+	let mut out = Vec::new();
+	for url in urls {
+		let start = Instant::now();
+		let result = client
+			.head(&url)
+			.timeout(timeout)
+			.send()
+			.await
+			.map(|x| (x.status(), start.elapsed()))
+			.map_err(anyhow::Error::from);
+		out.push(EndpointStatus { url, result });
+	}
+
+	out
+}
@@ -1,3 +1,7 @@
+use std::fmt::Display;
+use std::io::{stdout, Write};
+use std::time::Duration;
+
 use color_print::cprintln;
 
 /// Common trait for output from both the CLI and app
@@ -8,8 +12,85 @@ pub trait OutputTrait {
 	fn success(&mut self, msg: impl AsRef<str>);
 	/// Display an instruction message
 	fn instruction(&mut self, msg: impl AsRef<str>);
+	/// Display a warning message
+	fn warning(&mut self, msg: impl AsRef<str>);
 	/// Display a prompt to continue
 	fn continue_prompt(&mut self);
+	/// Display the progress of a download. Called repeatedly while the download runs
+	fn download_progress(&mut self, progress: &DownloadProgress);
+}
+
+/// Progress of a file download
+pub struct DownloadProgress {
+	/// Number of bytes downloaded so far
+	pub downloaded: u64,
+	/// Number of bytes that were already downloaded when the download was resumed
+	pub resumed: u64,
+	/// Size of the file, if the server sent it
+	pub total: Option<u64>,
+	/// Time since the download started
+	pub elapsed: Duration,
+	/// Whether the download has finished
+	pub done: bool,
+}
+
+impl DownloadProgress {
+	/// Get the percent of the file that has been downloaded, if the size is known
+	pub fn percent(&self) -> Option<f64> {
+		let total = self.total.filter(|x| *x > 0)?;
+		Some(self.downloaded as f64 / total as f64 * 100.0)
+	}
+
+	/// Get the average download speed in bytes per second, not counting resumed bytes
+	pub fn speed(&self) -> f64 {
+		let secs = self.elapsed.as_secs_f64();
+		if secs > 0.0 {
+			(self.downloaded - self.resumed) as f64 / secs
+		} else {
+			0.0
+		}
+	}
+
+	/// Get the estimated time until the download finishes, if the size is known
+	pub fn eta(&self) -> Option<Duration> {
+		let remaining = self.total?.saturating_sub(self.downloaded);
+		let speed = self.speed();
+		if speed > 0.0 {
+			Some(Duration::from_secs_f64(remaining as f64 / speed))
+		} else {
+			None
+		}
+	}
+}
+
+impl Display for DownloadProgress {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", format_bytes(self.downloaded as f64))?;
+		if let Some(total) = self.total {
+			write!(f, " / {}", format_bytes(total as f64))?;
+		}
+		if let Some(percent) = self.percent() {
+			write!(f, " ({percent:.1}%)")?;
+		}
+		write!(f, " at {}/s", format_bytes(self.speed()))?;
+		if let (Some(eta), false) = (self.eta(), self.done) {
+			let secs = eta.as_secs();
+			write!(f, ", {}m {:02}s left", secs / 60, secs % 60)?;
+		}
+		Ok(())
+	}
+}
+
+/// Format a number of bytes with a readable unit
+pub fn format_bytes(bytes: f64) -> String {
+	const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+	let mut value = bytes;
+	let mut unit = 0;
+	while value >= 1000.0 && unit < UNITS.len() - 1 {
+		value /= 1000.0;
+		unit += 1;
+	}
+	format!("{value:.1} {}", UNITS[unit])
 }
 
 /// Output object for both the CLI and app
@@ -31,9 +112,23 @@ impl OutputTrait for CommonOutput {
 		cprintln!("<s>{}", msg.as_ref());
 	}
 
+	fn warning(&mut self, msg: impl AsRef<str>) {
+		cprintln!("<s,y>{}", msg.as_ref());
+	}
+
 	fn continue_prompt(&mut self) {
 		crate::utils::continue_prompt();
 	}
+
+	fn download_progress(&mut self, progress: &DownloadProgress) {
+		// Overwrite the same line until the download is done
+		let mut stdout = stdout();
+		let _ = write!(stdout, "\r{progress:<60}");
+		if progress.done {
+			let _ = writeln!(stdout);
+		}
+		let _ = stdout.flush();
+	}
 }
 
 /// Null output
@@ -52,5 +147,13 @@ impl OutputTrait for NullOutput {
 		let _ = msg;
 	}
 
+	fn warning(&mut self, msg: impl AsRef<str>) {
+		let _ = msg;
+	}
+
 	fn continue_prompt(&mut self) {}
+
+	fn download_progress(&mut self, progress: &DownloadProgress) {
+		let _ = progress;
+	}
 }
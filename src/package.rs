@@ -1,568 +1,392 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::OnceLock;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
+use chrono::Utc;
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::assets::{
-	ADVANTAGESCOPE_ICON, CTRE_ICON, LIMELIGHT_ICON, NI_ICON, REV_ICON, WPILIB_ICON,
-};
-use crate::data::Data;
-use crate::install;
+use crate::assets::{self, DEFAULT_MANIFEST};
+use crate::data::{Data, InstalledPackage, PersistentData};
+use crate::dependencies::topological_sort;
+use crate::install::{self, InstallFrom};
+use crate::manifest::{InstallSpec, LaunchTarget, Manifest, PackageDef, ResolvedSource};
+use crate::net::with_network_overrides;
 use crate::output::OutputTrait;
+use crate::paths::PathContext;
+use crate::probe::DetectedVersion;
+use crate::season::Season;
 
-/// List of all packages, organized alphabetically by display name
-pub static ALL_PACKAGES: &[Package] = &[
-	Package::AdvantageScope,
-	Package::CacheCAD,
-	Package::Phoenix,
-	Package::DSLogViewer,
-	Package::Etcher,
-	Package::DriverStation,
-	Package::GameManual,
-	Package::GameTools,
-	Package::GithubDesktop,
-	Package::Glass,
-	Package::GRIP,
-	Package::LimelightFinder,
-	Package::PathPlanner,
-	Package::PathWeaver,
-	Package::PhoenixTuner,
-	Package::RadioUtility,
-	Package::REVClient,
-	Package::RIOImagingTool,
-	Package::TeamNumberSetter,
-	Package::Shuffleboard,
-	Package::TaskManager,
-	Package::WorbotsGithub,
-	Package::WorbotsWebsite,
-	Package::WPILib,
-	Package::DataLogTool,
-	Package::WPILibDocs,
-	Package::OutlineViewer,
-	Package::RobotBuilder,
-	Package::SysId,
-	Package::VSCode,
-];
-
-macro_rules! define_packages {
-	(
-		$all_pkgs:ident,
-		$((
-			$id:ident,
-			$name:literal,
-			$display_name:literal,
-			$short_desc:literal,
-			$icon:expr,
-			$parent:expr,
-			$can_install:literal,
-			$install:path,
-			$can_launch:literal,
-			$launch:path
-			$(,)*
-		));* $(;)*
-	) => {
-		#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
-		#[serde(rename_all = "snake_case")]
-		pub enum Package {
-			$(
-				#[serde(rename = $name)]
-				$id,
-			)*
-		}
+/// List of all packages in the registry, in manifest order
+pub static ALL_PACKAGES: AllPackages = AllPackages;
 
-		impl Display for Package {
-			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-				write!(
-					f,
-					"{}",
-					match self {
-						$(
-							Self::$id => $name,
-						)*
-					}
-				)
-			}
+/// The package registry, created from the manifest when the tool starts
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// The packages in the manifest and the sets of them that it defines
+struct Registry {
+	packages: Vec<Package>,
+	/// Packages that install-all installs, with the more important ones first
+	defaults: Vec<Package>,
+	/// Install profiles, mapping a profile name to the IDs of the packages in it
+	profiles: BTreeMap<String, Vec<String>>,
+}
+
+/// Fill the package registry from a manifest. Does nothing if the registry has already been used
+pub fn init_registry(manifest: Manifest) {
+	let _ = REGISTRY.set(leak_registry(manifest));
+}
+
+fn leak_registry(manifest: Manifest) -> Registry {
+	let defs: &'static [PackageDef] = Box::leak(manifest.packages.into_boxed_slice());
+	let packages: Vec<_> = defs.iter().map(Package).collect();
+	// The manifest was validated, so every ID has a package
+	let defaults = manifest
+		.default_packages
+		.iter()
+		.filter_map(|id| packages.iter().find(|x| x.id() == id).copied())
+		.collect();
+
+	Registry {
+		packages,
+		defaults,
+		profiles: manifest.profiles,
+	}
+}
+
+fn get_registry() -> &'static Registry {
+	REGISTRY.get_or_init(|| {
+		let manifest = serde_json::from_str(DEFAULT_MANIFEST)
+			.map_err(anyhow::Error::from)
+			.and_then(Manifest::from_value)
+			.expect("Default manifest is invalid");
+		leak_registry(manifest)
+	})
+}
+
+/// Get the packages that install-all installs, with the more important ones first
+pub fn get_default_packages() -> Vec<Package> {
+	get_registry().defaults.clone()
+}
+
+/// Get the install profiles that the manifest defines
+pub fn get_manifest_profiles() -> &'static BTreeMap<String, Vec<String>> {
+	&get_registry().profiles
+}
+
+/// Accessor for the package registry. Falls back to the embedded manifest
+/// if the registry was never initialized
+pub struct AllPackages;
+
+impl Deref for AllPackages {
+	type Target = [Package];
+
+	fn deref(&self) -> &Self::Target {
+		&get_registry().packages
+	}
+}
+
+/// Get the order to install packages in, including all of the dependencies of the packages
+pub fn resolve_install_order(packages: &[Package]) -> anyhow::Result<Vec<Package>> {
+	topological_sort(packages, |x| x.get_dependencies())
+}
+
+/// A package that can be installed or launched, defined by an entry in the manifest
+#[derive(Clone, Copy)]
+pub struct Package(&'static PackageDef);
+
+impl PartialEq for Package {
+	fn eq(&self, other: &Self) -> bool {
+		self.0.id == other.0.id
+	}
+}
+
+impl Eq for Package {}
+
+impl Hash for Package {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.0.id.hash(state);
+	}
+}
+
+impl Display for Package {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0.id)
+	}
+}
+
+impl Debug for Package {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		<Self as Display>::fmt(self, f)
+	}
+}
+
+impl FromStr for Package {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		ALL_PACKAGES
+			.iter()
+			.find(|x| x.id() == s)
+			.copied()
+			.ok_or(anyhow!(
+				"Unknown package type {s}. Must be one of {:?}",
+				&*ALL_PACKAGES
+			))
+	}
+}
+
+impl Serialize for Package {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.id())
+	}
+}
+
+impl<'de> Deserialize<'de> for Package {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let id = String::deserialize(deserializer)?;
+		Self::from_str(&id).map_err(serde::de::Error::custom)
+	}
+}
+
+// Display impls
+impl Package {
+	/// Get the unique ID of the package
+	pub fn id(&self) -> &'static str {
+		&self.0.id
+	}
+
+	/// Get the pretty display name of the package
+	pub fn display_name(&self) -> &'static str {
+		&self.0.display_name
+	}
+
+	/// Get the short description of the package
+	pub fn short_description(&self) -> &'static str {
+		&self.0.short_description
+	}
+
+	/// Get the icon of the package if it has one
+	pub fn get_icon(&self) -> Option<&'static [u8]> {
+		self.0.icon.as_deref().and_then(assets::get_icon)
+	}
+}
+
+impl Package {
+	/// Gets the parent package of this package, if it has one
+	pub fn get_parent(&self) -> Option<Self> {
+		self.0
+			.parent
+			.as_deref()
+			.and_then(|x| Self::from_str(x).ok())
+	}
+
+	/// Gets the packages that this package depends on, including its parent
+	pub fn get_dependencies(&self) -> Vec<Self> {
+		self.0
+			.all_dependencies()
+			.into_iter()
+			.filter_map(|x| Self::from_str(x).ok())
+			.collect()
+	}
+
+	/// Get the season that an install of the package belongs to,
+	/// or None if the package is shared between seasons
+	pub fn get_season(&self, season: Season) -> Option<Season> {
+		self.0.seasonal.then_some(season)
+	}
+
+	/// Get the directory that this tool keeps downloaded files for the package in
+	pub fn get_data_path(&self, data: &Data) -> anyhow::Result<PathBuf> {
+		let dir = data.get_data_directory()?.join(self.id());
+		Ok(match self.get_season(data.season) {
+			Some(season) => dir.join(season.to_string()),
+			None => dir,
+		})
+	}
+
+	/// Check if the package can be installed
+	pub fn can_install(&self) -> bool {
+		self.0.install.is_some()
+	}
+
+	/// Get the server that the package is downloaded from in a season
+	pub fn get_endpoint(&self, season: Season) -> Option<String> {
+		self.0.install.as_ref()?.source.get_endpoint(season)
+	}
+
+	/// Get how the package is installed, if it can be installed
+	pub fn get_install_spec(&self) -> Option<&'static InstallSpec> {
+		self.0.install.as_ref()
+	}
+
+	/// Install the package from a file source
+	pub async fn install(&self, data: &mut Data<'_>, from: &InstallFrom<'_>) -> anyhow::Result<()> {
+		data.out.progress(format!(
+			"Installing package {self} for season {}",
+			data.season
+		));
+		if let Some(spec) = &self.0.install {
+			let resolved = self
+				.with_network(install::install(spec, *self, from, data))
+				.await?;
+
+			let mut persistent = PersistentData::load(data)?;
+			persistent.record_install(InstalledPackage {
+				package: self.id().to_string(),
+				season: self.get_season(data.season),
+				version: resolved.version,
+				asset_url: resolved.url,
+				installed_at: Utc::now(),
+				install_path: self.get_install_path(data)?,
+			});
+			persistent
+				.save(data)
+				.context("Failed to record installed package")?;
 		}
 
-		impl Debug for Package {
-			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-				<Self as Display>::fmt(&self, f)
+		data.out.success("Package installed");
+
+		Ok(())
+	}
+
+	/// Uninstall the package, remove the files that this tool downloaded for it,
+	/// and remove its install record
+	pub async fn uninstall(&self, data: &mut Data<'_>) -> anyhow::Result<()> {
+		data.out.progress(format!("Uninstalling package {self}"));
+		match &self.0.uninstall {
+			Some(method) => {
+				let technology = self.0.install.as_ref().and_then(|x| x.technology);
+				install::uninstall(method, technology, *self, data)?
 			}
+			None => data.out.warning(format!(
+				"Package {self} has no uninstaller. Remove it manually from the Windows settings"
+			)),
 		}
 
-		impl FromStr for Package {
-			type Err = anyhow::Error;
-
-			fn from_str(s: &str) -> Result<Self, Self::Err> {
-				match s {
-					$(
-						$name => Ok(Self::$id),
-					)*
-					other => Err(anyhow!(
-						"Unknown package type {other}. Must be one of {:?}", $all_pkgs
-					)),
-				}
-			}
+		// Clean up installers and extracted files
+		let dir = self.get_data_path(data)?;
+		if dir.exists() {
+			std::fs::remove_dir_all(&dir).context("Failed to remove downloaded files")?;
 		}
 
-		// Display impls
-		impl Package {
-			/// Get the pretty display name of the package
-			pub fn display_name(&self) -> &'static str {
-				match self {
-					$(
-						Self::$id => $display_name,
-					)*
-				}
-			}
+		let mut persistent = PersistentData::load(data)?;
+		persistent.remove_install(*self, data.season);
+		persistent
+			.save(data)
+			.context("Failed to remove install record")?;
 
-			/// Get the short description of the package
-			pub fn short_description(&self) -> &'static str {
-				match self {
-					$(
-						Self::$id => $short_desc,
-					)*
-				}
-			}
+		data.out.success("Package uninstalled");
 
-			/// Get the icon of the package if it has one
-			pub fn get_icon(&self) -> Option<&'static [u8]> {
-				match self {
-					$(
-						Self::$id => $icon,
-					)*
-				}
-			}
-		}
+		Ok(())
+	}
 
-		impl Package {
-			/// Gets the parent package of this package, if it has one
-			pub fn get_parent(&self) -> Option<Self> {
-				match self {
-					$(
-						Self::$id => $parent,
-					)*
-				}
-			}
+	/// Get the directory that the package is installed in, if it is known
+	pub fn get_install_path(&self, data: &Data) -> anyhow::Result<Option<PathBuf>> {
+		let Some(LaunchTarget::Program { path, .. }) = &self.0.launch else {
+			return Ok(None);
+		};
+		let exec = PathContext::new(data)?.expand(path, *self);
+		Ok(exec.parent().map(Path::to_owned))
+	}
 
-			/// Check if the package can be installed
-			pub fn can_install(&self) -> bool {
-				match self {
-					$(
-						Self::$id => $can_install,
-					)*
-				}
-			}
+	/// Check if the package has its own version probe
+	pub fn has_version_probe(&self) -> bool {
+		self.0.version_probe.is_some()
+	}
 
-			/// Install the package
-			pub async fn install(&self, data: &mut Data<'_>) -> anyhow::Result<()> {
-				data.out.progress(format!("Installing package {self}"));
-				match self {
-					$(
-						Self::$id => $install(data).await?,
-					)*
-				}
+	/// Detect the version of the package that is installed. Packages without a version probe use
+	/// the probe of their parent. Returns None if there is no probe to use
+	pub fn detect_version(&self, paths: &PathContext) -> Option<DetectedVersion> {
+		match &self.0.version_probe {
+			Some(probe) => Some(probe.probe(paths, *self)),
+			None => self.get_parent()?.detect_version(paths),
+		}
+	}
 
-				data.out.success("Package installed");
+	/// Get the version of the package that is installed, preferring the detected version
+	/// over the version that was recorded when this tool installed it
+	pub fn get_installed_version(
+		&self,
+		paths: &PathContext,
+		persistent: &PersistentData,
+	) -> Option<String> {
+		if let Some(DetectedVersion::Version(version)) = self.detect_version(paths) {
+			return Some(version);
+		}
+		persistent
+			.get_installed(*self, paths.season())?
+			.version
+			.clone()
+	}
 
-				Ok(())
-			}
+	/// Check if the package is installed, either by detecting it or from the install record
+	pub fn is_installed(&self, paths: &PathContext, persistent: &PersistentData) -> bool {
+		let detected = self
+			.detect_version(paths)
+			.is_some_and(|x| x != DetectedVersion::NotInstalled);
+		detected || persistent.get_installed(*self, paths.season()).is_some()
+	}
 
-			/// Check if the package can be launched
-			pub fn can_launch(&self) -> bool {
-				match self {
-					$(
-						Self::$id => $can_launch,
-					)*
-				}
-			}
+	/// Get the version of the latest release of the package in a season.
+	/// Returns None if the package cannot be installed or its source is not versioned
+	pub async fn get_latest_version(
+		&self,
+		client: &Client,
+		season: Season,
+		out: &mut impl OutputTrait,
+	) -> anyhow::Result<Option<String>> {
+		let Some(spec) = &self.0.install else {
+			return Ok(None);
+		};
+		let resolved = self
+			.with_network(spec.source.resolve(client, season, out))
+			.await?;
+		Ok(resolved.version)
+	}
 
-			/// Launch the package if it can be launched
-			pub async fn launch(&self, data: &mut Data<'_>) -> anyhow::Result<()> {
-				data.out.progress(format!("Launching package {self}"));
-				match self {
-					$(
-						Self::$id => $launch(data)?,
-					)*
-				}
+	/// Find the file that would be installed for the package in a season.
+	/// Returns None if the package cannot be installed
+	pub async fn resolve_source(
+		&self,
+		data: &mut Data<'_>,
+		season: Season,
+	) -> anyhow::Result<Option<ResolvedSource>> {
+		let Some(spec) = &self.0.install else {
+			return Ok(None);
+		};
+		let resolved = self
+			.with_network(spec.source.resolve(&data.client, season, &mut *data.out))
+			.await
+			.with_context(|| format!("Failed to find installer for package {self}"))?;
+		Ok(Some(resolved))
+	}
 
-				data.out.success("Package launched");
+	/// Run a future that makes network requests for the package, using its network policy overrides
+	pub async fn with_network<F: Future>(&self, f: F) -> F::Output {
+		with_network_overrides(self.0.network.as_ref(), f).await
+	}
 
-				Ok(())
-			}
-		}
-	};
-}
+	/// Check if the package can be launched
+	pub fn can_launch(&self) -> bool {
+		self.0.launch.is_some()
+	}
 
-define_packages! {
-	ALL_PACKAGES,
-	(
-		AdvantageScope,
-		"advantagescope",
-		"AdvantageScope",
-		"A viewer for live robot telemetry and log files",
-		Some(ADVANTAGESCOPE_ICON),
-		None,
-		true,
-		install::advantagescope::install,
-		true,
-		install::advantagescope::launch,
-	);
-	(
-		REVClient,
-		"rev_client",
-		"REV Hardware Client",
-		"Updater and debugger for REV devices",
-		Some(REV_ICON),
-		None,
-		true,
-		install::rev_client::install,
-		true,
-		install::rev_client::launch,
-	);
-	(
-		LimelightFinder,
-		"limelight_finder",
-		"Limelight Finder",
-		"Tool to find Limelights on the robot network",
-		Some(LIMELIGHT_ICON),
-		None,
-		true,
-		install::limelight_finder::install,
-		true,
-		install::limelight_finder::launch,
-	);
-	(
-		GRIP,
-		"grip",
-		"GRIP",
-		"A graphical vision pipeline editor",
-		None,
-		None,
-		true,
-		install::grip::install,
-		true,
-		install::grip::launch,
-	);
-	(
-		Phoenix,
-		"phoenix",
-		"CTRE Phoenix",
-		"Tools for working with CTRE devices",
-		Some(CTRE_ICON),
-		None,
-		true,
-		install::phoenix::install,
-		false,
-		no_launch,
-	);
-	(
-		PathPlanner,
-		"pathplanner",
-		"PathPlanner",
-		"Autonomous path editor and generator",
-		None,
-		None,
-		true,
-		install::pathplanner::install,
-		true,
-		install::pathplanner::launch,
-	);
-	(
-		GithubDesktop,
-		"github_desktop",
-		"GitHub Desktop",
-		"Desktop app for GitHub, a website used to host robot code",
-		None,
-		None,
-		true,
-		install::github_desktop::install,
-		true,
-		install::github_desktop::launch,
-	);
-	(
-		Etcher,
-		"etcher",
-		"Etcher",
-		"Flashes OS images to drives. Used to flash the roboRIO 2",
-		None,
-		None,
-		true,
-		install::etcher::install,
-		true,
-		install::etcher::launch,
-	);
-	(
-		PhoenixTuner,
-		"phoenix_tuner",
-		"Phoenix Tuner",
-		"Allows viewing, debugging, and configuration of devices on a CAN network",
-		Some(CTRE_ICON),
-		Some(Self::Phoenix),
-		false,
-		no_install,
-		true,
-		install::phoenix::launch_phoenix_tuner,
-	);
-	(
-		WPILib,
-		"wpilib",
-		"WPILib",
-		"The official tools for developing FRC robots",
-		Some(WPILIB_ICON),
-		None,
-		true,
-		install::wpilib::install,
-		false,
-		no_launch,
-	);
-	(
-		VSCode,
-		"vscode",
-		"WPILib VSCode",
-		"Microsoft's code editor configured for robot development",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_vscode,
-	);
-	(
-		DataLogTool,
-		"data_log_tool",
-		"WPILib Data Log Tool",
-		"Viewer and downloader for robot log files",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_data_log_tool,
-	);
-	(
-		TeamNumberSetter,
-		"team_number_setter",
-		"roboRIO Team Number Setter",
-		"Simple tool used to set the team number on a roboRIO",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_team_number_setter,
-	);
-	(
-		Glass,
-		"glass",
-		"Glass",
-		"Dashboard for drivers and simulation",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_glass,
-	);
-	(
-		OutlineViewer,
-		"outline_viewer",
-		"WPILib Outline Viewer",
-		"A simple NetworkTables editor",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_outline_viewer,
-	);
-	(
-		PathWeaver,
-		"pathweaver",
-		"PathWeaver",
-		"The WPILib autonomous path editor",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_pathweaver,
-	);
-	(
-		Shuffleboard,
-		"shuffleboard",
-		"Shuffleboard",
-		"An interactive and customizable robot dashboard",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_shuffleboard,
-	);
-	(
-		SysId,
-		"sysid",
-		"WPILib SysId",
-		"A tool for analyzing and tuning robot control systems",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_sysid,
-	);
-	(
-		RobotBuilder,
-		"robotbuilder",
-		"WPILib RobotBuilder",
-		"Tool for generating WPILib robot projects",
-		Some(WPILIB_ICON),
-		Some(Self::WPILib),
-		false,
-		no_install,
-		true,
-		install::wpilib::launch_robotbuilder,
-	);
-	(
-		GameTools,
-		"game_tools",
-		"FRC Game Tools",
-		"Official tools for the robot, including the radio utility and driver station",
-		Some(NI_ICON),
-		None,
-		true,
-		install::game_tools::install,
-		false,
-		no_launch,
-	);
-	(
-		DriverStation,
-		"driver_station",
-		"FRC Driver Station",
-		"The station for drivers used to control the robot",
-		Some(NI_ICON),
-		Some(Self::GameTools),
-		false,
-		no_install,
-		true,
-		install::game_tools::launch_driver_station,
-	);
-	(
-		DSLogViewer,
-		"ds_log_viewer",
-		"DS Log File Viewer",
-		"A tool to view Driver Station log files",
-		Some(NI_ICON),
-		Some(Self::GameTools),
-		false,
-		no_install,
-		true,
-		install::game_tools::launch_ds_log_viewer,
-	);
-	(
-		RadioUtility,
-		"radio_utility",
-		"FRC Radio Configuration Utility",
-		"Flash and configure radio devices",
-		Some(NI_ICON),
-		Some(Self::GameTools),
-		false,
-		no_install,
-		true,
-		install::game_tools::launch_radio_utility,
-	);
-	(
-		RIOImagingTool,
-		"rio_imaging_tool",
-		"roboRIO Imaging Tool",
-		"Flash and update the roboRIO",
-		Some(NI_ICON),
-		Some(Self::GameTools),
-		false,
-		no_install,
-		true,
-		install::game_tools::launch_rio_imaging_tool,
-	);
-	(
-		WPILibDocs,
-		"wpilib_docs",
-		"WPILib Docs",
-		"Documentation for WPILib and developing FRC robots",
-		Some(WPILIB_ICON),
-		None,
-		false,
-		no_install,
-		true,
-		install::shortcuts::open_wpilib_docs,
-	);
-	(
-		WorbotsGithub,
-		"worbots_github",
-		"WorBots GitHub",
-		"GitHub organization for the WorBots team",
-		None,
-		None,
-		false,
-		no_install,
-		true,
-		install::shortcuts::open_worbots_github,
-	);
-	(
-		GameManual,
-		"game_manual",
-		"FRC Game Manual",
-		"Official manual for the FRC game",
-		None,
-		None,
-		false,
-		no_install,
-		true,
-		install::shortcuts::open_game_manual,
-	);
-	(
-		CacheCAD,
-		"cachecad",
-		"CacheCAD",
-		"A file management interface for Google Drive",
-		None,
-		None,
-		true,
-		install::cachecad::install,
-		true,
-		install::cachecad::launch,
-	);
-	(
-		TaskManager,
-		"task_manager",
-		"Task Manager",
-		"The Windows Task Manager",
-		None,
-		None,
-		false,
-		no_install,
-		true,
-		install::misc::launch_task_manager,
-	);
-	(
-		WorbotsWebsite,
-		"worbots_website",
-		"WorBots Website",
-		"Official website for the WorBots team",
-		None,
-		None,
-		false,
-		no_install,
-		true,
-		install::shortcuts::open_worbots_website,
-	);
-}
+	/// Launch the package if it can be launched
+	pub async fn launch(&self, data: &mut Data<'_>) -> anyhow::Result<()> {
+		data.out.progress(format!("Launching package {self}"));
+		if let Some(target) = &self.0.launch {
+			install::launch(target, *self, data)?;
+		}
 
-async fn no_install(_: &mut Data<'_>) -> anyhow::Result<()> {
-	Ok(())
-}
+		data.out.success("Package launched");
 
-fn no_launch(_: &mut Data<'_>) -> anyhow::Result<()> {
-	Ok(())
+		Ok(())
+	}
 }
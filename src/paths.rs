@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use crate::data::Data;
+use crate::package::Package;
+use crate::season::Season;
+use crate::utils::get_base_dirs;
+
+/// Locations that paths in the manifest are resolved against. Paths can contain the placeholders
+/// `{data}`, `{local_data}`, `{local_programs}`, and `{package_data}`, along with the season placeholders
+pub struct PathContext {
+	/// Directory that absolute paths are resolved under, or None to use the real filesystem
+	root: Option<PathBuf>,
+	data: PathBuf,
+	local_data: PathBuf,
+	/// The data directory of this tool
+	tool_data: PathBuf,
+	season: Season,
+}
+
+impl PathContext {
+	/// Create a context for the real filesystem
+	pub fn new(data: &Data) -> anyhow::Result<Self> {
+		let dirs = get_base_dirs()?;
+		Ok(Self {
+			root: None,
+			data: dirs.data_dir().to_owned(),
+			local_data: dirs.data_local_dir().to_owned(),
+			tool_data: data.get_data_directory()?.to_owned(),
+			season: data.season,
+		})
+	}
+
+	/// Create a context where every path is inside of a directory tree, such as a test fixture.
+	/// Absolute paths have their drive letter turned into a directory, so `C:/x` becomes `<root>/C/x`
+	pub fn with_root(root: &Path, season: Season) -> Self {
+		Self {
+			root: Some(root.to_owned()),
+			data: root.join("data"),
+			local_data: root.join("local_data"),
+			tool_data: root.join("tool_data"),
+			season,
+		}
+	}
+
+	/// Get the season that paths are resolved for
+	pub fn season(&self) -> Season {
+		self.season
+	}
+
+	/// Replace the placeholders in a path and resolve it
+	pub fn expand(&self, path: &str, package: Package) -> PathBuf {
+		let path = self.season.expand(path);
+		// Placeholder directories are already inside of the root
+		if path.starts_with('{') {
+			let out = path
+				.replace("{data}", &self.data.to_string_lossy())
+				.replace("{local_data}", &self.local_data.to_string_lossy())
+				.replace(
+					"{local_programs}",
+					&self.local_data.join("Programs").to_string_lossy(),
+				)
+				.replace(
+					"{package_data}",
+					&self.tool_data.join(package.id()).to_string_lossy(),
+				);
+			PathBuf::from(out)
+		} else {
+			self.resolve(&path)
+		}
+	}
+
+	fn resolve(&self, path: &str) -> PathBuf {
+		match &self.root {
+			Some(root) => {
+				let relative = match path.split_once(':') {
+					Some((drive, rest)) if drive.len() == 1 => format!("{drive}{rest}"),
+					_ => path.to_string(),
+				};
+				root.join(relative.trim_start_matches(['/', '\\']))
+			}
+			None => PathBuf::from(path),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use super::*;
+
+	fn get_context(root: &Path) -> PathContext {
+		PathContext::with_root(root, Season::from_str("2024").unwrap())
+	}
+
+	#[test]
+	fn test_absolute_paths_are_under_root() {
+		let root = Path::new("/fake");
+		let paths = get_context(root);
+		let package = Package::from_str("wpilib").unwrap();
+		assert_eq!(
+			paths.expand("C:/Users/Public/wpilib/{season}", package),
+			root.join("C/Users/Public/wpilib/2024")
+		);
+		assert_eq!(
+			paths.expand("C:\\Program Files\\NI", package),
+			root.join("C\\Program Files\\NI")
+		);
+	}
+
+	#[test]
+	fn test_placeholders_are_under_root() {
+		let root = Path::new("/fake");
+		let paths = get_context(root);
+		let package = Package::from_str("pathplanner").unwrap();
+		assert_eq!(
+			paths.expand("{local_programs}/PathPlanner", package),
+			root.join("local_data/Programs/PathPlanner")
+		);
+		assert_eq!(
+			paths.expand("{package_data}/extracted", package),
+			root.join("tool_data/pathplanner/extracted")
+		);
+		assert_eq!(
+			paths.expand("{data}/Elastic {season_short}", package),
+			root.join("data/Elastic 24")
+		);
+	}
+}
@@ -0,0 +1,303 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::package::Package;
+use crate::paths::PathContext;
+use crate::version::compare_versions;
+
+/// Way of finding the installed version of a package. Paths are resolved using a PathContext
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VersionProbe {
+	/// The package is installed if a path exists, but the version is unknown
+	Exists { path: String },
+	/// Read the version from the first line of a text file
+	File { path: String },
+	/// Read the version field of a package.json file
+	PackageJson { path: String },
+	/// Read the file version from the resources of a Windows executable
+	ExeVersion { path: String },
+	/// Use the name of the newest subdirectory of a directory, such as a year
+	/// folder or a version folder in a Maven repository
+	Directory {
+		path: String,
+		/// Prefix that subdirectories must have, which is removed from the version
+		#[serde(default)]
+		prefix: String,
+	},
+}
+
+/// Result of probing for the installed version of a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectedVersion {
+	NotInstalled,
+	/// The package is installed, but its version could not be determined
+	Unknown,
+	Version(String),
+}
+
+impl VersionProbe {
+	/// Probe for the installed version of a package
+	pub fn probe(&self, paths: &PathContext, package: Package) -> DetectedVersion {
+		match self {
+			Self::Exists { path } => {
+				if paths.expand(path, package).exists() {
+					DetectedVersion::Unknown
+				} else {
+					DetectedVersion::NotInstalled
+				}
+			}
+			Self::File { path } => probe_file(&paths.expand(path, package), |path| {
+				let contents = std::fs::read_to_string(path).ok()?;
+				let line = contents.lines().next()?.trim();
+				(!line.is_empty()).then(|| line.to_string())
+			}),
+			Self::PackageJson { path } => probe_file(&paths.expand(path, package), |path| {
+				let contents = std::fs::read_to_string(path).ok()?;
+				let json: serde_json::Value = serde_json::from_str(&contents).ok()?;
+				json.get("version")?.as_str().map(ToString::to_string)
+			}),
+			Self::ExeVersion { path } => probe_file(&paths.expand(path, package), read_exe_version),
+			Self::Directory { path, prefix } => {
+				let Ok(entries) = std::fs::read_dir(paths.expand(path, package)) else {
+					return DetectedVersion::NotInstalled;
+				};
+				let newest = entries
+					.filter_map(Result::ok)
+					.filter(|x| x.path().is_dir())
+					.filter_map(|x| {
+						let name = x.file_name().to_string_lossy().to_string();
+						name.strip_prefix(prefix.as_str()).map(ToString::to_string)
+					})
+					.max_by(|a, b| compare_versions(a, b));
+				match newest {
+					Some(version) => DetectedVersion::Version(version),
+					None => DetectedVersion::NotInstalled,
+				}
+			}
+		}
+	}
+}
+
+/// Probe a file that holds a version, which is unknown if the file cannot be read
+fn probe_file(path: &Path, read: impl FnOnce(&Path) -> Option<String>) -> DetectedVersion {
+	if !path.exists() {
+		return DetectedVersion::NotInstalled;
+	}
+
+	match read(path) {
+		Some(version) => DetectedVersion::Version(version),
+		None => DetectedVersion::Unknown,
+	}
+}
+
+/// Read the file version from the VS_FIXEDFILEINFO structure of a Windows executable.
+/// The file is scanned in chunks since some executables are very large
+fn read_exe_version(path: &Path) -> Option<String> {
+	const SIGNATURE: [u8; 4] = 0xFEEF04BDu32.to_le_bytes();
+	const STRUCT_VERSION: u32 = 0x00010000;
+	// Signature, struct version, and the two file version fields
+	const NEEDED: usize = 16;
+
+	let mut file = File::open(path).ok()?;
+	let mut buf = vec![0u8; 64 * 1024];
+	let mut carry = 0;
+	loop {
+		let read = file.read(&mut buf[carry..]).ok()?;
+		if read == 0 {
+			return None;
+		}
+		let len = carry + read;
+
+		for pos in 0..len.saturating_sub(NEEDED - 1) {
+			if buf[pos..pos + 4] != SIGNATURE {
+				continue;
+			}
+			let dword = |i: usize| {
+				let start = pos + i * 4;
+				u32::from_le_bytes(buf[start..start + 4].try_into().expect("Slice is 4 bytes"))
+			};
+			if dword(1) != STRUCT_VERSION {
+				continue;
+			}
+
+			let (ms, ls) = (dword(2), dword(3));
+			let parts = [ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF];
+			let out = if parts[3] == 0 {
+				format!("{}.{}.{}", parts[0], parts[1], parts[2])
+			} else {
+				format!("{}.{}.{}.{}", parts[0], parts[1], parts[2], parts[3])
+			};
+			return Some(out);
+		}
+
+		// Keep the end of the chunk in case the structure is split between chunks
+		carry = len.min(NEEDED - 1);
+		buf.copy_within(len - carry..len, 0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use tempfile::TempDir;
+
+	use super::*;
+	use crate::season::Season;
+
+	/// Create an empty fake filesystem root and a context for it
+	fn create_root() -> (TempDir, PathContext) {
+		let dir = tempfile::tempdir().unwrap();
+		let paths = PathContext::with_root(dir.path(), Season::from_str("2024").unwrap());
+		(dir, paths)
+	}
+
+	/// Write a file under the root, creating its parent directories
+	fn write(root: &TempDir, path: &str, contents: &[u8]) {
+		let path = root.path().join(path);
+		std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+		std::fs::write(path, contents).unwrap();
+	}
+
+	fn probe(probe: VersionProbe, paths: &PathContext) -> DetectedVersion {
+		probe.probe(paths, Package::from_str("wpilib").unwrap())
+	}
+
+	fn version(version: &str) -> DetectedVersion {
+		DetectedVersion::Version(version.to_string())
+	}
+
+	/// Create the bytes of a VS_FIXEDFILEINFO structure with a file version
+	fn version_info(parts: [u32; 4]) -> Vec<u8> {
+		let mut out = 0xFEEF04BDu32.to_le_bytes().to_vec();
+		out.extend(0x00010000u32.to_le_bytes());
+		out.extend(((parts[0] << 16) | parts[1]).to_le_bytes());
+		out.extend(((parts[2] << 16) | parts[3]).to_le_bytes());
+		out
+	}
+
+	#[test]
+	fn test_exists() {
+		let (root, paths) = create_root();
+		let probe_exists = || {
+			probe(
+				VersionProbe::Exists {
+					path: "C:/Program Files/Tool/tool.exe".into(),
+				},
+				&paths,
+			)
+		};
+		assert_eq!(probe_exists(), DetectedVersion::NotInstalled);
+		write(&root, "C/Program Files/Tool/tool.exe", b"");
+		assert_eq!(probe_exists(), DetectedVersion::Unknown);
+	}
+
+	#[test]
+	fn test_file() {
+		let (root, paths) = create_root();
+		let probe_file = || {
+			probe(
+				VersionProbe::File {
+					path: "C:/Users/Public/wpilib/{season}/version.txt".into(),
+				},
+				&paths,
+			)
+		};
+		assert_eq!(probe_file(), DetectedVersion::NotInstalled);
+		write(
+			&root,
+			"C/Users/Public/wpilib/2024/version.txt",
+			b" 2024.3.2 \r\nmore",
+		);
+		assert_eq!(probe_file(), version("2024.3.2"));
+		write(&root, "C/Users/Public/wpilib/2024/version.txt", b"");
+		assert_eq!(probe_file(), DetectedVersion::Unknown);
+	}
+
+	#[test]
+	fn test_package_json() {
+		let (root, paths) = create_root();
+		let probe_json = || {
+			probe(
+				VersionProbe::PackageJson {
+					path: "{local_programs}/Tool/resources/package.json".into(),
+				},
+				&paths,
+			)
+		};
+		assert_eq!(probe_json(), DetectedVersion::NotInstalled);
+		write(
+			&root,
+			"local_data/Programs/Tool/resources/package.json",
+			br#"{"name": "tool", "version": "3.1.0"}"#,
+		);
+		assert_eq!(probe_json(), version("3.1.0"));
+		write(
+			&root,
+			"local_data/Programs/Tool/resources/package.json",
+			br#"{"name": "tool"}"#,
+		);
+		assert_eq!(probe_json(), DetectedVersion::Unknown);
+	}
+
+	#[test]
+	fn test_exe_version() {
+		let (root, paths) = create_root();
+		let probe_exe = || {
+			probe(
+				VersionProbe::ExeVersion {
+					path: "C:/Program Files/Tool/tool.exe".into(),
+				},
+				&paths,
+			)
+		};
+		assert_eq!(probe_exe(), DetectedVersion::NotInstalled);
+
+		let mut exe = b"MZ".to_vec();
+		exe.extend(version_info([24, 0, 1, 0]));
+		write(&root, "C/Program Files/Tool/tool.exe", &exe);
+		assert_eq!(probe_exe(), version("24.0.1"));
+
+		// The structure is split between the chunks that the file is read in
+		let mut exe = vec![0; 64 * 1024 - 6];
+		exe.extend(version_info([1, 2, 3, 4]));
+		write(&root, "C/Program Files/Tool/tool.exe", &exe);
+		assert_eq!(probe_exe(), version("1.2.3.4"));
+
+		write(
+			&root,
+			"C/Program Files/Tool/tool.exe",
+			b"MZ no version here",
+		);
+		assert_eq!(probe_exe(), DetectedVersion::Unknown);
+	}
+
+	#[test]
+	fn test_directory() {
+		let (root, paths) = create_root();
+		let probe_dir = || {
+			probe(
+				VersionProbe::Directory {
+					path: "C:/Users/Public/maven/tool".into(),
+					prefix: "v".into(),
+				},
+				&paths,
+			)
+		};
+		assert_eq!(probe_dir(), DetectedVersion::NotInstalled);
+		std::fs::create_dir_all(root.path().join("C/Users/Public/maven/tool")).unwrap();
+		assert_eq!(probe_dir(), DetectedVersion::NotInstalled);
+
+		for dir in ["v2.9.0", "v2.10.1", "other"] {
+			std::fs::create_dir_all(root.path().join("C/Users/Public/maven/tool").join(dir))
+				.unwrap();
+		}
+		// Files are not versions even if they have the prefix
+		write(&root, "C/Users/Public/maven/tool/v9.0.0", b"");
+		assert_eq!(probe_dir(), version("2.10.1"));
+	}
+}
@@ -0,0 +1,28 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+
+use crate::config::Config;
+use crate::package::{get_manifest_profiles, Package};
+
+/// Get all of the available profiles. Profiles in the config replace profiles from the manifest with the same name
+pub fn get_profiles(config: &Config) -> BTreeMap<String, Vec<String>> {
+	let mut out = get_manifest_profiles().clone();
+	out.extend(config.profiles.clone());
+	out
+}
+
+/// Get the packages in a profile
+pub fn get_profile(config: &Config, name: &str) -> anyhow::Result<Vec<Package>> {
+	let profiles = get_profiles(config);
+	let profile = profiles.get(name).ok_or(anyhow!(
+		"Unknown profile {name}. Must be one of {:?}",
+		profiles.keys().collect::<Vec<_>>()
+	))?;
+
+	profile
+		.iter()
+		.map(|x| Package::from_str(x).with_context(|| format!("Invalid package in profile {name}")))
+		.collect()
+}
@@ -0,0 +1,45 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use chrono::{Datelike, Local};
+use serde::{Deserialize, Serialize};
+
+/// An FRC season, identified by its year. Manifest strings can contain the placeholders
+/// `{season}` for the full year and `{season_short}` for the last two digits
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(transparent)]
+pub struct Season(u16);
+
+impl Season {
+	/// Get the season that is happening now. Seasons start with kickoff in January,
+	/// so this is the current year
+	pub fn current() -> Self {
+		Self(Local::now().year() as u16)
+	}
+
+	/// Replace the season placeholders in a string
+	pub fn expand(&self, text: &str) -> String {
+		text.replace("{season}", &self.to_string())
+			.replace("{season_short}", &format!("{:02}", self.0 % 100))
+	}
+}
+
+impl Display for Season {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl FromStr for Season {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let year: u16 = s.parse().context("Season must be a year")?;
+		// FRC started in 1992
+		if !(1992..=9999).contains(&year) {
+			bail!("Invalid season {year}");
+		}
+		Ok(Self(year))
+	}
+}
@@ -0,0 +1,420 @@
+use std::fmt::Display;
+
+use regex::Regex;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::utils::{GithubRelease, GithubReleaseAsset};
+
+/// File extensions of files that are published next to installers but are never installers themselves
+const METADATA_EXTENSIONS: &[&str] = &[
+	".blockmap",
+	".sig",
+	".asc",
+	".sha256",
+	".sha512",
+	".md5",
+	".yml",
+	".yaml",
+	".json",
+	".txt",
+];
+
+/// Rules for choosing a Github release and the asset in it to install
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct AssetRules {
+	/// Regexes that the asset name must all match
+	pub include: Vec<Pattern>,
+	/// Regexes that the asset name must not match
+	pub exclude: Vec<Pattern>,
+	/// The architecture to prefer. Assets for other architectures are never chosen
+	pub arch: Option<Arch>,
+	/// File extensions that the asset can have, from most to least preferred
+	pub extension: Vec<String>,
+	/// Whether prereleases can be chosen
+	pub prerelease: bool,
+	/// Whether drafts can be chosen. Drafts are only visible with a Github token
+	pub draft: bool,
+	/// Constraint that the version in the release tag must meet, like `>=2.0, <3`
+	pub version: Option<VersionReq>,
+}
+
+impl AssetRules {
+	/// Check if the rules can only be met by searching through all releases instead of using the latest one
+	pub fn needs_release_list(&self) -> bool {
+		self.prerelease || self.draft || self.version.is_some()
+	}
+
+	/// Check if a release can be chosen, returning why not if it can't
+	pub fn check_release(&self, release: &GithubRelease) -> Result<(), String> {
+		if release.draft && !self.draft {
+			return Err("is a draft".into());
+		}
+		if release.prerelease && !self.prerelease {
+			return Err("is a prerelease".into());
+		}
+		if let Some(req) = &self.version {
+			let version = parse_tag_version(&release.tag_name)
+				.ok_or(format!("tag {} does not have a version", release.tag_name))?;
+			if !req.matches(&version) {
+				return Err(format!("version {version} does not match {req}"));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Score every asset in a release and choose the best one. Assets must also contain all of
+	/// the given substring patterns
+	pub fn select(&self, release: &GithubRelease, patterns: &[String]) -> Selection {
+		let candidates: Vec<_> = release
+			.assets
+			.iter()
+			.map(|asset| Candidate {
+				name: asset.name.clone(),
+				outcome: self.score(&asset.name, patterns),
+			})
+			.collect();
+
+		// Ties go to the asset that Github lists first
+		let mut chosen = None;
+		let mut best = i32::MIN;
+		for (i, candidate) in candidates.iter().enumerate() {
+			if let Outcome::Accepted { score, .. } = &candidate.outcome {
+				if *score > best {
+					best = *score;
+					chosen = Some(i);
+				}
+			}
+		}
+
+		Selection {
+			tag: release.tag_name.clone(),
+			chosen,
+			candidates,
+		}
+	}
+
+	/// Score an asset name, or reject it with the reason
+	fn score(&self, name: &str, patterns: &[String]) -> Outcome {
+		let lower = name.to_lowercase();
+		let mut score = 0;
+		let mut reasons = Vec::new();
+
+		if let Some(pattern) = patterns.iter().find(|x| !name.contains(x.as_str())) {
+			return Outcome::Rejected(format!("does not contain {pattern:?}"));
+		}
+		if let Some(pattern) = self.include.iter().find(|x| !x.0.is_match(name)) {
+			return Outcome::Rejected(format!("does not match {}", pattern.0));
+		}
+		if let Some(pattern) = self.exclude.iter().find(|x| x.0.is_match(name)) {
+			return Outcome::Rejected(format!("matches excluded {}", pattern.0));
+		}
+
+		if self.extension.is_empty() {
+			if let Some(ext) = METADATA_EXTENSIONS.iter().find(|x| lower.ends_with(*x)) {
+				return Outcome::Rejected(format!("is a {ext} metadata file"));
+			}
+		} else {
+			let position = self
+				.extension
+				.iter()
+				.position(|x| lower.ends_with(&x.to_lowercase()));
+			let Some(position) = position else {
+				return Outcome::Rejected(format!(
+					"does not have an extension in {:?}",
+					self.extension
+				));
+			};
+			score += 10 * (self.extension.len() - position) as i32;
+			reasons.push(format!("has extension {}", self.extension[position]));
+		}
+
+		if let Some(arch) = self.arch {
+			let named = Arch::find_in_name(&lower);
+			if named.contains(&arch) {
+				score += 20;
+				reasons.push(format!("is for {arch}"));
+			} else if let Some(other) = named.first() {
+				return Outcome::Rejected(format!("is for {other}"));
+			} else {
+				reasons.push("does not name an architecture".into());
+			}
+		}
+
+		if reasons.is_empty() {
+			reasons.push("matches all rules".into());
+		}
+
+		Outcome::Accepted { score, reasons }
+	}
+}
+
+/// A regex from the manifest
+pub struct Pattern(Regex);
+
+impl<'de> Deserialize<'de> for Pattern {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let pattern = String::deserialize(deserializer)?;
+		let regex = Regex::new(&pattern).map_err(serde::de::Error::custom)?;
+		Ok(Self(regex))
+	}
+}
+
+/// CPU architecture that an asset is built for
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Arch {
+	X64,
+	X86,
+	Arm64,
+}
+
+impl Arch {
+	/// Names that assets use for the architecture
+	fn get_aliases(&self) -> &'static [&'static str] {
+		match self {
+			Self::X64 => &["x64", "x86_64", "amd64", "win64"],
+			Self::X86 => &["x86", "ia32", "i386", "i686", "win32"],
+			Self::Arm64 => &["arm64", "aarch64"],
+		}
+	}
+
+	/// Find the architectures that a lowercase asset name mentions
+	fn find_in_name(name: &str) -> Vec<Self> {
+		// Split on everything but underscores so that x86_64 stays together
+		let words: Vec<_> = name
+			.split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+			.collect();
+		[Self::X64, Self::X86, Self::Arm64]
+			.into_iter()
+			.filter(|arch| words.iter().any(|x| arch.get_aliases().contains(x)))
+			.collect()
+	}
+}
+
+impl Display for Arch {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.get_aliases()[0])
+	}
+}
+
+/// Parse the version in a release tag like `v2024.1.1` or `rhc-1.6`, filling in missing parts with zero
+pub fn parse_tag_version(tag: &str) -> Option<Version> {
+	let start = tag.find(|c: char| c.is_ascii_digit())?;
+	let version = &tag[start..];
+	if let Ok(version) = Version::parse(version) {
+		return Some(version);
+	}
+
+	let (core, pre) = match version.split_once('-') {
+		Some((core, pre)) => (core, Some(pre)),
+		None => (version, None),
+	};
+	let mut parts = core.split('.').map(str::parse::<u64>);
+	let major = parts.next()?.ok()?;
+	let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+	let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+	if parts.next().is_some() {
+		return None;
+	}
+
+	let mut out = Version::new(major, minor, patch);
+	if let Some(pre) = pre {
+		out.pre = semver::Prerelease::new(pre).ok()?;
+	}
+	Some(out)
+}
+
+/// The assets of a release that were considered and the one that was chosen
+pub struct Selection {
+	/// Tag of the release
+	pub tag: String,
+	/// Index of the chosen asset in the candidates, which matches its index in the release
+	pub chosen: Option<usize>,
+	pub candidates: Vec<Candidate>,
+}
+
+impl Selection {
+	/// Get the chosen asset from the release that was scored
+	pub fn get_asset<'r>(&self, release: &'r GithubRelease) -> Option<&'r GithubReleaseAsset> {
+		release.assets.get(self.chosen?)
+	}
+}
+
+impl Display for Selection {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Release {}", self.tag)?;
+		for (i, candidate) in self.candidates.iter().enumerate() {
+			let marker = if Some(i) == self.chosen { "*" } else { " " };
+			write!(f, "\n {marker} {}: ", candidate.name)?;
+			match &candidate.outcome {
+				Outcome::Accepted { score, reasons } => {
+					write!(f, "score {score}, {}", reasons.join(", "))?
+				}
+				Outcome::Rejected(reason) => write!(f, "rejected, {reason}")?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// An asset that was considered
+pub struct Candidate {
+	pub name: String,
+	pub outcome: Outcome,
+}
+
+/// Whether an asset can be chosen and why
+pub enum Outcome {
+	Accepted { score: i32, reasons: Vec<String> },
+	Rejected(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::{json, Value};
+
+	use super::*;
+
+	fn release(tag: &str, prerelease: bool, draft: bool, assets: &[&str]) -> GithubRelease {
+		let assets: Vec<_> = assets
+			.iter()
+			.map(|name| {
+				json!({
+					"name": name,
+					"url": format!("https://api.github.com/repos/team/app/releases/assets/{name}"),
+					"browser_download_url": format!("https://github.com/team/app/releases/download/{tag}/{name}"),
+					"digest": null,
+				})
+			})
+			.collect();
+		serde_json::from_value(json!({
+			"tag_name": tag,
+			"prerelease": prerelease,
+			"draft": draft,
+			"assets": assets,
+		}))
+		.unwrap()
+	}
+
+	fn rules(value: Value) -> AssetRules {
+		serde_json::from_value(value).unwrap()
+	}
+
+	fn chosen_name(selection: &Selection) -> Option<&str> {
+		Some(&selection.candidates[selection.chosen?].name)
+	}
+
+	#[test]
+	fn test_rejects_metadata_files() {
+		let release = release(
+			"v1.0.0",
+			false,
+			false,
+			&[
+				"App-1.0.0.exe.blockmap",
+				"App-1.0.0.exe.sig",
+				"App-1.0.0.exe",
+			],
+		);
+		let selection = AssetRules::default().select(&release, &[]);
+		assert_eq!(chosen_name(&selection), Some("App-1.0.0.exe"));
+		assert!(matches!(
+			&selection.candidates[0].outcome,
+			Outcome::Rejected(reason) if reason == "is a .blockmap metadata file"
+		));
+		assert!(matches!(
+			&selection.candidates[1].outcome,
+			Outcome::Rejected(reason) if reason == "is a .sig metadata file"
+		));
+		assert_eq!(
+			selection.get_asset(&release).unwrap().browser_download_url,
+			"https://github.com/team/app/releases/download/v1.0.0/App-1.0.0.exe"
+		);
+	}
+
+	#[test]
+	fn test_prefers_arch() {
+		let release = release(
+			"v1.0.0",
+			false,
+			false,
+			&[
+				"app-setup.exe",
+				"app-arm64-setup.exe",
+				"app-x86_64-setup.exe",
+			],
+		);
+		let selection = rules(json!({"arch": "x64"})).select(&release, &[]);
+		assert_eq!(chosen_name(&selection), Some("app-x86_64-setup.exe"));
+		assert!(matches!(
+			&selection.candidates[0].outcome,
+			Outcome::Accepted { score: 0, .. }
+		));
+		assert!(matches!(
+			&selection.candidates[1].outcome,
+			Outcome::Rejected(reason) if reason == "is for arm64"
+		));
+
+		// An asset without an architecture is used when none match
+		let selection = rules(json!({"arch": "x86"})).select(&release, &[]);
+		assert_eq!(chosen_name(&selection), Some("app-setup.exe"));
+	}
+
+	#[test]
+	fn test_check_release() {
+		let stable = release("v2024.1.1", false, false, &[]);
+		let prerelease = release("v2024.2.0-beta-1", true, false, &[]);
+		let draft = release("v2024.2.0", false, true, &[]);
+
+		let default = AssetRules::default();
+		assert_eq!(default.check_release(&stable), Ok(()));
+		assert_eq!(
+			default.check_release(&prerelease),
+			Err("is a prerelease".into())
+		);
+		assert_eq!(default.check_release(&draft), Err("is a draft".into()));
+
+		let allow = rules(json!({"prerelease": true, "draft": true}));
+		assert!(allow.needs_release_list());
+		assert_eq!(allow.check_release(&prerelease), Ok(()));
+		assert_eq!(allow.check_release(&draft), Ok(()));
+
+		let version = rules(json!({"version": ">=2024.2"}));
+		assert_eq!(
+			version.check_release(&stable),
+			Err("version 2024.1.1 does not match >=2024.2".into())
+		);
+		assert_eq!(
+			version.check_release(&release("latest", false, false, &[])),
+			Err("tag latest does not have a version".into())
+		);
+	}
+
+	#[test]
+	fn test_explanation() {
+		let release = release(
+			"v1.2.0",
+			false,
+			false,
+			&[
+				"app-1.2.0.zip",
+				"app-1.2.0.exe",
+				"app-1.2.0.msi",
+				"app-1.2.0.exe.sig",
+			],
+		);
+		let selection = rules(json!({"extension": [".msi", ".exe"]})).select(&release, &[]);
+		let expected = [
+			"Release v1.2.0",
+			"   app-1.2.0.zip: rejected, does not have an extension in [\".msi\", \".exe\"]",
+			"   app-1.2.0.exe: score 10, has extension .exe",
+			" * app-1.2.0.msi: score 20, has extension .msi",
+			"   app-1.2.0.exe.sig: rejected, does not have an extension in [\".msi\", \".exe\"]",
+		];
+		assert_eq!(selection.to_string(), expected.join("\n"));
+	}
+}
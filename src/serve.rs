@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ErrorKind, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use color_print::cprintln;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+use crate::cache::Cache;
+use crate::utils::get_mirror_path;
+
+/// Serve the download cache over HTTP so that other computers can use it as a mirror.
+/// Files are served at the mirror path of their upstream URL, and `/` lists the cached files
+pub fn serve(cache: Cache, address: &str) -> anyhow::Result<()> {
+	let server = Server::http(address).map_err(|e| anyhow!("Failed to start server: {e}"))?;
+	let index = Arc::new(CacheIndex::new(cache)?);
+	for request in server.incoming_requests() {
+		let index = index.clone();
+		// Downloads can take minutes, so each request gets its own thread
+		std::thread::spawn(move || {
+			if let Err(e) = handle_request(&index, request) {
+				// Clients going away in the middle of a download is normal
+				if !is_disconnect(&e) {
+					cprintln!("<r>Failed to respond to request: {e:?}");
+				}
+			}
+		});
+	}
+
+	Ok(())
+}
+
+/// How long the index is used for before a request for a missing file rebuilds it
+const INDEX_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Index of the cached files by the mirror path that they are served at, so that requests
+/// don't have to read every cache entry
+struct CacheIndex {
+	cache: Cache,
+	files: Mutex<(Instant, HashMap<String, CachedFile>)>,
+}
+
+#[derive(Clone)]
+struct CachedFile {
+	key: String,
+	path: PathBuf,
+}
+
+impl CacheIndex {
+	fn new(cache: Cache) -> anyhow::Result<Self> {
+		let files = Self::build(&cache)?;
+		Ok(Self {
+			cache,
+			files: Mutex::new((Instant::now(), files)),
+		})
+	}
+
+	fn build(cache: &Cache) -> anyhow::Result<HashMap<String, CachedFile>> {
+		let mut out = HashMap::new();
+		// Entries are listed most recently used first, and that one is served when a URL has
+		// several entries
+		for entry in cache.list()? {
+			out.entry(get_mirror_path(&entry.url))
+				.or_insert_with(|| CachedFile {
+					path: cache.get_file_path(&entry),
+					key: entry.key,
+				});
+		}
+
+		Ok(out)
+	}
+
+	/// Find the file served at a mirror path. The index is rebuilt when the file is missing
+	/// and the index is old, since files can be added to the cache while serving
+	fn get(&self, mirror_path: &str) -> anyhow::Result<Option<CachedFile>> {
+		let mut files = self.files.lock().expect("Cache index lock was poisoned");
+		let (built, files) = &mut *files;
+		if !files.contains_key(mirror_path) && built.elapsed() > INDEX_MAX_AGE {
+			*files = Self::build(&self.cache)?;
+			*built = Instant::now();
+		}
+
+		// The file may have been removed from the cache since the index was built
+		Ok(files.get(mirror_path).filter(|x| x.path.exists()).cloned())
+	}
+}
+
+fn handle_request(index: &CacheIndex, request: Request) -> anyhow::Result<()> {
+	if !matches!(request.method(), Method::Get | Method::Head) {
+		request.respond(Response::empty(StatusCode(405)))?;
+		return Ok(());
+	}
+
+	if request.url() == "/" {
+		let body = serde_json::to_string_pretty(&index.cache.list()?)?;
+		let response = Response::from_string(body)
+			.with_header(make_header("Content-Type", "application/json"));
+		request.respond(response)?;
+		return Ok(());
+	}
+
+	let entry = match request.url().strip_prefix('/') {
+		Some(path) => index.get(path)?,
+		None => None,
+	};
+	let Some(entry) = entry else {
+		request.respond(Response::empty(StatusCode(404)))?;
+		return Ok(());
+	};
+
+	let mut file = File::open(&entry.path)?;
+	let len = file.metadata()?.len();
+	// Entries are content-addressed, so the key identifies the exact file
+	let etag = format!("\"{}\"", entry.key);
+	let mut headers = vec![
+		make_header("ETag", &etag),
+		make_header("Accept-Ranges", "bytes"),
+	];
+
+	// Serve the rest of the file to clients that are resuming a download
+	let start = get_range_start(&request, &etag).filter(|x| *x < len);
+	let status = match start {
+		Some(start) => {
+			let range = format!("bytes {start}-{}/{len}", len - 1);
+			headers.push(make_header("Content-Range", &range));
+			206
+		}
+		None => 200,
+	};
+	let start = start.unwrap_or_default();
+	file.seek(SeekFrom::Start(start))?;
+
+	let response = Response::new(
+		StatusCode(status),
+		headers,
+		file,
+		Some((len - start) as usize),
+		None,
+	)
+	// Chunked responses have no length, which clients need to show progress and resume
+	.with_chunked_threshold(usize::MAX);
+	request.respond(response)?;
+
+	Ok(())
+}
+
+/// Get the first byte of a `bytes=<start>-` range request, ignoring it if the If-Range
+/// validator does not match
+fn get_range_start(request: &Request, etag: &str) -> Option<u64> {
+	let get_header = |name: &'static str| {
+		request
+			.headers()
+			.iter()
+			.find(|x| x.field.equiv(name))
+			.map(|x| x.value.as_str())
+	};
+	if get_header("If-Range").is_some_and(|x| x != etag) {
+		return None;
+	}
+
+	let range = get_header("Range")?.strip_prefix("bytes=")?;
+	let (start, end) = range.split_once('-')?;
+	// Only open ended ranges are used for resuming
+	if !end.is_empty() {
+		return None;
+	}
+	start.parse().ok()
+}
+
+/// Check if an error is from the client closing the connection
+fn is_disconnect(error: &anyhow::Error) -> bool {
+	error.downcast_ref::<std::io::Error>().is_some_and(|x| {
+		matches!(
+			x.kind(),
+			ErrorKind::BrokenPipe | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+		)
+	})
+}
+
+fn make_header(name: &str, value: &str) -> Header {
+	Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("Header should be valid")
+}
@@ -0,0 +1,244 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand_core::OsRng;
+use serde_json::Value;
+
+use crate::assets::TEAM_MANIFEST_KEY;
+use crate::data::Data;
+use crate::manifest::Manifest;
+use crate::output::OutputTrait;
+use crate::utils::download_bytes;
+
+/// Name of the cached team manifest in the data directory
+const CACHE_FILE: &str = "team_manifest.json";
+/// Name of the cached team manifest signature in the data directory
+const CACHE_SIGNATURE_FILE: &str = "team_manifest.json.sig";
+
+/// A team manifest whose signature has been checked
+struct SignedManifest {
+	contents: String,
+	signature: Vec<u8>,
+	published: Option<DateTime<Utc>>,
+}
+
+/// Get the team manifest to use as the base of the package registry. The manifest is fetched
+/// from the configured URL and falls back to the last good cached copy. Returns None if there is
+/// no usable team manifest, in which case the embedded manifest should be used
+pub async fn get_team_manifest(data: &mut Data<'_>) -> anyhow::Result<Option<String>> {
+	let Some(url) = data.config.manifest_url.clone() else {
+		return Ok(None);
+	};
+	let data_dir = data.get_data_directory()?.to_owned();
+	let cached = read_cache(&data_dir);
+
+	data.out.progress("Fetching team manifest");
+	let fetched = fetch(data, &url).await.and_then(|x| {
+		check_not_older(&x, cached.as_ref().ok().and_then(Option::as_ref)).map(|_| x)
+	});
+	match fetched {
+		Ok(manifest) => {
+			write_cache(&data_dir, &manifest).context("Failed to cache team manifest")?;
+			return Ok(Some(manifest.contents));
+		}
+		Err(e) => data.out.warning(format!(
+			"Could not use the remote team manifest: {e:#}. Falling back to the cached copy"
+		)),
+	}
+
+	Ok(use_cache(data, cached))
+}
+
+/// Get the last good cached team manifest without fetching it, for commands that must work
+/// offline. Returns None if there is no usable team manifest
+pub fn get_cached_team_manifest(data: &mut Data<'_>) -> anyhow::Result<Option<String>> {
+	if data.config.manifest_url.is_none() {
+		return Ok(None);
+	}
+	let cached = read_cache(data.get_data_directory()?);
+
+	Ok(use_cache(data, cached))
+}
+
+/// Get the contents of the cached team manifest, warning if it can't be used
+fn use_cache(
+	data: &mut Data<'_>,
+	cached: anyhow::Result<Option<SignedManifest>>,
+) -> Option<String> {
+	match cached {
+		Ok(Some(manifest)) => Some(manifest.contents),
+		Ok(None) => {
+			data.out
+				.warning("No cached team manifest. Falling back to the built-in manifest");
+			None
+		}
+		Err(e) => {
+			data.out.warning(format!(
+				"Cached team manifest is invalid: {e:#}. Falling back to the built-in manifest"
+			));
+			None
+		}
+	}
+}
+
+/// Download a team manifest and its signature and check that they are valid
+async fn fetch(data: &mut Data<'_>, url: &str) -> anyhow::Result<SignedManifest> {
+	let manifest = download_bytes(&data.client, url, &mut *data.out)
+		.await
+		.context("Failed to download manifest")?;
+	let signature = download_bytes(&data.client, &format!("{url}.sig"), &mut *data.out)
+		.await
+		.context("Failed to download manifest signature")?;
+
+	check(&manifest, &signature)
+}
+
+/// Verify the signature of a team manifest and make sure that it is a valid manifest
+fn check(manifest: &[u8], signature: &[u8]) -> anyhow::Result<SignedManifest> {
+	verify(manifest, signature)?;
+	let contents = String::from_utf8(manifest.to_vec()).context("Manifest is not UTF-8")?;
+	let parsed = Manifest::from_value(serde_json::from_str(&contents)?)?;
+
+	Ok(SignedManifest {
+		contents,
+		signature: signature.to_vec(),
+		published: parsed.published,
+	})
+}
+
+/// Make sure that a fetched manifest is not older than the cached one, so that an old manifest
+/// that was signed with the same key can't be served again to undo a change
+fn check_not_older(
+	manifest: &SignedManifest,
+	cached: Option<&SignedManifest>,
+) -> anyhow::Result<()> {
+	let Some(cached) = cached.and_then(|x| x.published) else {
+		return Ok(());
+	};
+	match manifest.published {
+		Some(published) if published >= cached => Ok(()),
+		Some(published) => bail!(
+			"Manifest was published at {published}, which is older than the cached manifest from {cached}"
+		),
+		None => {
+			bail!("Manifest has no publish time, but the cached manifest was published at {cached}")
+		}
+	}
+}
+
+/// Verify a manifest against the embedded public key
+fn verify(manifest: &[u8], signature: &[u8]) -> anyhow::Result<()> {
+	let key = get_built_in_key()?;
+	let signature = Signature::from_slice(signature).context("Signature is malformed")?;
+	key.verify_strict(manifest, &signature)
+		.context("Signature does not match")?;
+
+	Ok(())
+}
+
+/// Get the public key that is built into the tool
+fn get_built_in_key() -> anyhow::Result<VerifyingKey> {
+	let hex: String = TEAM_MANIFEST_KEY
+		.lines()
+		.map(str::trim)
+		.filter(|x| !x.starts_with('#'))
+		.collect();
+	if hex.is_empty() {
+		bail!("No team manifest key is built into this tool. See src/assets/team_manifest.pub for how to add one");
+	}
+	let key = decode_key(&hex).context("Built-in public key is invalid")?;
+	VerifyingKey::from_bytes(&key).context("Built-in public key is invalid")
+}
+
+fn write_cache(data_dir: &Path, manifest: &SignedManifest) -> anyhow::Result<()> {
+	std::fs::write(data_dir.join(CACHE_FILE), &manifest.contents)?;
+	std::fs::write(data_dir.join(CACHE_SIGNATURE_FILE), &manifest.signature)?;
+	Ok(())
+}
+
+/// Read the cached team manifest, checking it again in case it was modified
+fn read_cache(data_dir: &Path) -> anyhow::Result<Option<SignedManifest>> {
+	let manifest_path = data_dir.join(CACHE_FILE);
+	let signature_path = data_dir.join(CACHE_SIGNATURE_FILE);
+	if !manifest_path.exists() || !signature_path.exists() {
+		return Ok(None);
+	}
+
+	let manifest = std::fs::read(manifest_path)?;
+	let signature = std::fs::read(signature_path)?;
+	let manifest = check(&manifest, &signature)?;
+
+	Ok(Some(manifest))
+}
+
+/// Generate a key pair for signing team manifests and save the private key to a file.
+/// Returns the public key in hex, which is what goes in team_manifest.pub
+pub fn keygen(path: &Path) -> anyhow::Result<String> {
+	if path.exists() {
+		bail!(
+			"{} already exists. Remove it first if you want to replace the key",
+			path.display()
+		);
+	}
+	let key = SigningKey::generate(&mut OsRng);
+	std::fs::write(path, encode_hex(&key.to_bytes())).context("Failed to write key file")?;
+
+	Ok(encode_hex(key.verifying_key().as_bytes()))
+}
+
+/// The result of signing a team manifest
+pub struct SignOutput {
+	/// Path to the signature, which is uploaded next to the manifest
+	pub signature_path: PathBuf,
+	/// Whether the key matches the one built into this tool
+	pub trusted: bool,
+}
+
+/// Sign a team manifest with the private key in a file. The publish time in the manifest is set
+/// to now and the signature is written next to it with `.sig` appended
+pub fn sign(manifest_path: &Path, key_path: &Path) -> anyhow::Result<SignOutput> {
+	let key = std::fs::read_to_string(key_path).context("Failed to read key file")?;
+	let key = SigningKey::from_bytes(&decode_key(key.trim()).context("Key file is invalid")?);
+
+	let contents = std::fs::read_to_string(manifest_path).context("Failed to read manifest")?;
+	let mut manifest: Value =
+		serde_json::from_str(&contents).context("Failed to parse manifest")?;
+	manifest
+		.as_object_mut()
+		.ok_or(anyhow!("Manifest is not a JSON object"))?
+		.insert("published".into(), Utc::now().to_rfc3339().into());
+	Manifest::from_value(manifest.clone())?;
+
+	let contents = serde_json::to_string_pretty(&manifest)?;
+	let signature = key.sign(contents.as_bytes());
+	let mut signature_path = manifest_path.as_os_str().to_owned();
+	signature_path.push(".sig");
+	let signature_path = PathBuf::from(signature_path);
+	std::fs::write(manifest_path, contents).context("Failed to write manifest")?;
+	std::fs::write(&signature_path, signature.to_bytes()).context("Failed to write signature")?;
+
+	let trusted = get_built_in_key().is_ok_and(|x| x == key.verifying_key());
+	Ok(SignOutput {
+		signature_path,
+		trusted,
+	})
+}
+
+/// Decode a 32 byte key from hex
+fn decode_key(hex: &str) -> anyhow::Result<[u8; 32]> {
+	if hex.len() != 64 {
+		bail!("Key must be 64 hex characters");
+	}
+	let mut out = [0; 32];
+	for (i, byte) in out.iter_mut().enumerate() {
+		let digits = hex.get(i * 2..i * 2 + 2).ok_or(anyhow!("Key is not hex"))?;
+		*byte = u8::from_str_radix(digits, 16).context("Key is not hex")?;
+	}
+	Ok(out)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|x| format!("{x:02x}")).collect()
+}
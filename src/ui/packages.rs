@@ -4,6 +4,7 @@ use native_windows_derive::NwgPartial;
 use native_windows_gui as nwg;
 
 use crate::assets::EMPTY_BMP;
+use crate::install::InstallFrom;
 use crate::utils::tokio_exec;
 use crate::{data::Data, utils::tokio_exec_deferred, output::CommonOutput};
 use crate::package::{ALL_PACKAGES, Package};
@@ -76,8 +77,8 @@ impl PackagesUI {
 		self.data_view.select_item(
 			ALL_PACKAGES
 				.iter()
-				.position(|x| x == &Package::PhoenixTuner)
-				.expect("Phoenix tuner not in all packages"),
+				.position(|x| x.id() == "phoenix_tuner")
+				.unwrap_or_default(),
 			true
 		);
 
@@ -187,7 +188,7 @@ impl PackageDetailsPane {
 			tokio_exec_deferred(async move {
 				let mut out = CommonOutput;
 				let mut data = Data::new(&mut out).expect("Failed to create application data");
-				pkg.install(&mut data).await.expect("Failed to install package");
+				pkg.install(&mut data, &InstallFrom::Latest).await.expect("Failed to install package");
 			}).expect("Failed to execute task");
 
 			self.install_button.set_text("Install/Update");
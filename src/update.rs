@@ -0,0 +1,52 @@
+use crate::data::{Data, PersistentData};
+use crate::output::OutputTrait;
+use crate::package::{Package, ALL_PACKAGES};
+use crate::paths::PathContext;
+use crate::version::compare_versions;
+
+/// An installed package that has a newer release available
+pub struct OutdatedPackage {
+	pub package: Package,
+	pub installed: String,
+	pub latest: String,
+}
+
+/// Find the installed packages that have a newer release available. Packages whose installed
+/// version is unknown or whose source is not versioned are skipped
+pub async fn find_outdated(data: &mut Data<'_>) -> anyhow::Result<Vec<OutdatedPackage>> {
+	let paths = PathContext::new(data)?;
+	let persistent = PersistentData::load(data)?;
+
+	let mut out = Vec::new();
+	for package in ALL_PACKAGES.iter().filter(|x| x.can_install()) {
+		let Some(installed) = package.get_installed_version(&paths, &persistent) else {
+			continue;
+		};
+
+		data.out
+			.progress(format!("Checking for updates to package {package}"));
+		let latest = match package
+			.get_latest_version(&data.client, data.season, &mut *data.out)
+			.await
+		{
+			Ok(Some(latest)) => latest,
+			Ok(None) => continue,
+			Err(e) => {
+				data.out.warning(format!(
+					"Failed to check package {package} for updates: {e:#}"
+				));
+				continue;
+			}
+		};
+
+		if compare_versions(&latest, &installed).is_gt() {
+			out.push(OutdatedPackage {
+				package: *package,
+				installed,
+				latest,
+			});
+		}
+	}
+
+	Ok(out)
+}
@@ -1,65 +1,354 @@
 use std::{
+	fmt::Display,
+	fs::{File, OpenOptions},
 	future::Future,
-	io::{stdin, stdout, Read, Write},
+	io::{stdin, stdout, BufWriter, ErrorKind, Read, Write},
 	os::windows::process::CommandExt,
 	path::{Path, PathBuf},
 	process::Command,
+	sync::{PoisonError, RwLock},
 	thread::JoinHandle,
+	time::{Duration, Instant},
 };
 
-use anyhow::{anyhow, Context};
-use directories::ProjectDirs;
-use reqwest::Client;
-use serde::{de::DeserializeOwned, Deserialize};
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Local};
+use directories::{BaseDirs, ProjectDirs};
+use reqwest::header::{
+	HeaderName, ACCEPT, CONTENT_RANGE, ETAG, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE,
+	RETRY_AFTER,
+};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-/// Wrapper around Client::get
-pub async fn download(
-	client: &Client,
-	url: impl reqwest::IntoUrl,
-) -> anyhow::Result<reqwest::Response> {
-	let out = client
+use crate::net::{get_network_policy, retry, with_timeout};
+use crate::output::{DownloadProgress, OutputTrait};
+
+/// Create a GET request with the headers that every request uses
+fn request(client: &Client, url: &str) -> reqwest::RequestBuilder {
+	client
 		.get(url)
 		.header("User-Agent", "Worbots 4145 Setup Tool")
+}
+
+/// URL of a mirror that downloads try before their upstream URL
+static MIRROR: RwLock<Option<String>> = RwLock::new(None);
+
+/// Set the mirror that downloads try before their upstream URL
+pub fn set_mirror(url: Option<String>) {
+	*MIRROR.write().unwrap_or_else(PoisonError::into_inner) = url;
+}
+
+/// Get the mirror that downloads try first, if there is one
+pub fn get_mirror() -> Option<String> {
+	MIRROR
+		.read()
+		.unwrap_or_else(PoisonError::into_inner)
+		.clone()
+}
+
+/// Get the path that a mirror serves the file for an upstream URL at
+pub fn get_mirror_path(url: &str) -> String {
+	format!("files/{}", get_url_hash(url))
+}
+
+/// Get the SHA-256 hash of a URL in hex, for use in file names
+fn get_url_hash(url: &str) -> String {
+	let mut hasher = Sha256::new();
+	hasher.update(url.as_bytes());
+	format!("{:x}", hasher.finalize())
+}
+
+/// Get the URL to download a file from, which is the mirror if there is one that has the file
+async fn get_download_url(client: &Client, url: &str) -> String {
+	let Some(mirror) = get_mirror() else {
+		return url.to_string();
+	};
+
+	let mirrored = format!("{}/{}", mirror.trim_end_matches('/'), get_mirror_path(url));
+	// The mirror is only an optimization, so a slow mirror is not worth waiting long for
+	let found = client
+		.head(&mirrored)
+		.timeout(get_network_policy().get_connect_timeout())
 		.send()
+		.await
+		.is_ok_and(|x| x.status().is_success());
+	if found {
+		mirrored
+	} else {
+		url.to_string()
+	}
+}
+
+/// Send a request, failing if the server does not respond within the timeout of the network policy
+async fn send(req: reqwest::RequestBuilder) -> anyhow::Result<reqwest::Response> {
+	let response = with_timeout(&get_network_policy(), req.send())
 		.await
 		.context("Failed to download")?
-		.error_for_status()?;
+		.context("Failed to download")?;
+	Ok(response)
+}
+
+/// Wrapper around Client::get that does not use the mirror
+async fn download_direct(client: &Client, url: &str) -> anyhow::Result<reqwest::Response> {
+	let out = send(request(client, url)).await?.error_for_status()?;
 	Ok(out)
 }
 
 /// Download bytes
 pub async fn download_bytes(
 	client: &Client,
-	url: impl reqwest::IntoUrl,
+	url: &str,
+	out: &mut impl OutputTrait,
 ) -> anyhow::Result<bytes::Bytes> {
-	let bytes = download(client, url).await?.bytes().await?;
-	Ok(bytes)
+	let url = get_download_url(client, url).await;
+	let url = &url;
+	retry(out, || async move {
+		let response = download_direct(client, url).await?;
+		let bytes = with_timeout(&get_network_policy(), response.bytes()).await??;
+		Ok(bytes)
+	})
+	.await
 }
 
-/// Download a file to a path
+/// Download a file to a path, streaming it to a temporary file that replaces the path when the
+/// download finishes. If the download is interrupted, the temporary file is kept and the next
+/// download of the same URL resumes from where it stopped. Returns the SHA-256 checksum of the
+/// file in hex, which is found while it downloads
 pub async fn download_file(
 	client: &Client,
-	url: impl reqwest::IntoUrl,
+	url: &str,
 	path: &Path,
-) -> anyhow::Result<()> {
-	let bytes = download_bytes(client, url)
-		.await
-		.context("Failed to download file bytes")?;
-	std::fs::write(path, bytes).context("Failed to write to file")?;
-	Ok(())
+	out: &mut impl OutputTrait,
+) -> anyhow::Result<String> {
+	let temp_path = get_partial_path(path, "part");
+	let meta_path = get_partial_path(path, "part.json");
+
+	// Retries resume from the partial download
+	let policy = get_network_policy();
+	let mut attempt = 0;
+	let sha256 = loop {
+		match download_to(client, url, &temp_path, &meta_path, out).await {
+			Ok(sha256) => break sha256,
+			Err(e) if policy.should_retry(&e, attempt) => {
+				policy.wait_to_retry(&e, attempt, out).await;
+				attempt += 1;
+			}
+			Err(e) => return Err(e),
+		}
+	};
+
+	std::fs::rename(&temp_path, path).context("Failed to move downloaded file into place")?;
+	let _ = std::fs::remove_file(&meta_path);
+	Ok(sha256)
 }
 
-/// Download and parse into JSON
-pub async fn download_json<D: DeserializeOwned>(
+/// Stream a download into a file, resuming a partial download if there is one.
+/// Returns the SHA-256 checksum of the whole file in hex
+async fn download_to(
 	client: &Client,
-	url: impl reqwest::IntoUrl,
-) -> anyhow::Result<D> {
-	let out = download(client, url)
+	url: &str,
+	path: &Path,
+	meta_path: &Path,
+	out: &mut impl OutputTrait,
+) -> anyhow::Result<String> {
+	let policy = get_network_policy();
+	// The partial download is for the upstream URL even if it comes from the mirror
+	let partial = PartialDownload::load(path, meta_path, url);
+	let source_url = get_download_url(client, url).await;
+	let (mut response, resumed) = match partial {
+		Some((meta, len)) => match resume_download(client, &source_url, &meta, len).await? {
+			Some(x) => x,
+			None => return file_sha256(path),
+		},
+		None => (download_direct(client, &source_url).await?, 0),
+	};
+
+	// Save what is needed to resume before writing anything
+	let meta = PartialDownload {
+		url: url.to_string(),
+		etag: get_header(&response, ETAG),
+		last_modified: get_header(&response, LAST_MODIFIED),
+		length: response.content_length().map(|x| x + resumed),
+	};
+	meta.save(meta_path)?;
+
+	let mut hasher = Sha256::new();
+	let file = if resumed > 0 {
+		// The part that was already downloaded is hashed before the rest is added to it
+		let mut partial = File::open(path).context("Failed to open partial download")?;
+		std::io::copy(&mut partial, &mut hasher).context("Failed to read partial download")?;
+		OpenOptions::new().append(true).open(path)
+	} else {
+		File::create(path)
+	}
+	.context("Failed to create file")?;
+	let mut file = BufWriter::new(file);
+
+	let start = Instant::now();
+	let mut last_update = start;
+	let mut progress = DownloadProgress {
+		downloaded: resumed,
+		resumed,
+		total: meta.length,
+		elapsed: Duration::ZERO,
+		done: false,
+	};
+	while let Some(chunk) = with_timeout(&policy, response.chunk())
 		.await?
-		.json()
-		.await
-		.context("Failed to parse response JSON")?;
-	Ok(out)
+		.context("Failed to download file bytes")?
+	{
+		file.write_all(&chunk).context("Failed to write to file")?;
+		hasher.update(&chunk);
+		progress.downloaded += chunk.len() as u64;
+
+		// Limit how often progress is shown so that it doesn't slow down the download
+		if last_update.elapsed() >= PROGRESS_INTERVAL {
+			last_update = Instant::now();
+			progress.elapsed = start.elapsed();
+			out.download_progress(&progress);
+		}
+	}
+	file.flush().context("Failed to write to file")?;
+
+	progress.elapsed = start.elapsed();
+	progress.done = true;
+	out.download_progress(&progress);
+
+	if let Some(length) = meta.length {
+		if progress.downloaded != length {
+			// This is a connection problem, so the download is retried
+			let message = format!(
+				"Download ended early after {} of {length} bytes",
+				progress.downloaded
+			);
+			return Err(std::io::Error::new(ErrorKind::UnexpectedEof, message).into());
+		}
+	}
+
+	Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Request the rest of a partial download. Returns the response and the offset it starts at,
+/// or None if the partial file is already complete. Falls back to downloading the whole file
+/// if the file has changed or the server does not support ranges
+async fn resume_download(
+	client: &Client,
+	url: &str,
+	meta: &PartialDownload,
+	len: u64,
+) -> anyhow::Result<Option<(reqwest::Response, u64)>> {
+	let mut req = request(client, url).header(RANGE, format!("bytes={len}-"));
+	// If-Range makes the server send the whole file if it changed since the partial download
+	if let Some(validator) = meta.etag.as_ref().or(meta.last_modified.as_ref()) {
+		req = req.header(IF_RANGE, validator);
+	}
+	let response = send(req).await?;
+
+	match response.status() {
+		StatusCode::PARTIAL_CONTENT if get_content_range_start(&response) == Some(len) => {
+			Ok(Some((response, len)))
+		}
+		StatusCode::RANGE_NOT_SATISFIABLE if meta.length == Some(len) => Ok(None),
+		StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+			Ok(Some((download_direct(client, url).await?, 0)))
+		}
+		_ => Ok(Some((response.error_for_status()?, 0))),
+	}
+}
+
+/// Information about a partial download, stored next to it so that it can be resumed
+#[derive(Serialize, Deserialize)]
+struct PartialDownload {
+	url: String,
+	etag: Option<String>,
+	last_modified: Option<String>,
+	/// Length of the whole file, if the server sent it
+	length: Option<u64>,
+}
+
+impl PartialDownload {
+	/// Load the metadata for a partial download and get the length of the partial file.
+	/// Returns None if there is nothing that can be safely resumed
+	fn load(path: &Path, meta_path: &Path, url: &str) -> Option<(Self, u64)> {
+		let contents = std::fs::read_to_string(meta_path).ok()?;
+		let meta: Self = serde_json::from_str(&contents).ok()?;
+		let len = std::fs::metadata(path).ok()?.len();
+		// Without a validator there is no way to know that the file did not change
+		let has_validator = meta.etag.is_some() || meta.last_modified.is_some();
+		if meta.url != url || !has_validator || len == 0 {
+			return None;
+		}
+		Some((meta, len))
+	}
+
+	fn save(&self, meta_path: &Path) -> anyhow::Result<()> {
+		let contents = serde_json::to_string(self)?;
+		std::fs::write(meta_path, contents).context("Failed to write download metadata")?;
+		Ok(())
+	}
+}
+
+/// Get a header from a response as a string
+fn get_header(response: &reqwest::Response, name: HeaderName) -> Option<String> {
+	let value = response.headers().get(name)?.to_str().ok()?;
+	Some(value.to_string())
+}
+
+/// Get the first byte of a Content-Range header, like `bytes 100-999/1000`
+fn get_content_range_start(response: &reqwest::Response) -> Option<u64> {
+	let range = get_header(response, CONTENT_RANGE)?;
+	let (start, _) = range.strip_prefix("bytes ")?.split_once('-')?;
+	start.parse().ok()
+}
+
+/// How often download progress is reported
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Get the path of a temporary file that goes with a download
+fn get_partial_path(path: &Path, extension: &str) -> PathBuf {
+	let mut name = path.file_name().unwrap_or_default().to_owned();
+	name.push(".");
+	name.push(extension);
+	path.with_file_name(name)
+}
+
+/// Get the SHA-256 checksum of a file in hex
+pub fn file_sha256(path: &Path) -> anyhow::Result<String> {
+	let mut file = File::open(path).context("Failed to open file")?;
+	let mut hasher = Sha256::new();
+	std::io::copy(&mut file, &mut hasher).context("Failed to read file")?;
+	Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Environment variable that a Github token can be set with
+pub const GITHUB_TOKEN_VAR: &str = "GITHUB_TOKEN";
+
+/// Name of the directory in the data directory that Github API responses are cached in
+pub const GITHUB_CACHE_DIR: &str = "github";
+
+/// Longest time to wait when Github asks to retry a request later
+const MAX_GITHUB_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// Settings for requests to the Github API
+static GITHUB: RwLock<GithubSettings> = RwLock::new(GithubSettings {
+	token: None,
+	cache_dir: None,
+});
+
+/// Settings for requests to the Github API
+pub struct GithubSettings {
+	/// Token to authenticate with, which raises the rate limit
+	pub token: Option<String>,
+	/// Directory to cache responses in. Requests for cached responses are conditional,
+	/// so they don't count against the rate limit if nothing changed
+	pub cache_dir: Option<PathBuf>,
+}
+
+/// Set the settings for requests to the Github API
+pub fn set_github_settings(settings: GithubSettings) {
+	*GITHUB.write().unwrap_or_else(PoisonError::into_inner) = settings;
 }
 
 /// Download a latest Github release
@@ -67,27 +356,214 @@ pub async fn download_github_release(
 	client: &Client,
 	user: &str,
 	repo: &str,
+	out: &mut impl OutputTrait,
 ) -> anyhow::Result<GithubRelease> {
 	let url = format!("https://api.github.com/repos/{user}/{repo}/releases/latest");
-	let out = download_json(client, url).await?;
-	Ok(out)
+	github_request(client, &url, out).await
 }
 
-/// Get the list of releases for a Github project
+/// Number of releases in each page of the Github release list
+pub const GITHUB_RELEASES_PER_PAGE: usize = 100;
+
+/// Get a page of the list of releases for a Github project, newest first. Pages start at 1
 pub async fn get_github_releases(
 	client: &Client,
 	user: &str,
 	repo: &str,
+	page: u32,
+	out: &mut impl OutputTrait,
 ) -> anyhow::Result<Vec<GithubRelease>> {
-	let url = format!("https://api.github.com/repos/{user}/{repo}/releases");
-	let out = download_json(client, url).await?;
+	let url = format!(
+		"https://api.github.com/repos/{user}/{repo}/releases?per_page={GITHUB_RELEASES_PER_PAGE}&page={page}"
+	);
+	github_request(client, &url, out).await
+}
+
+/// Request JSON from the Github API, retrying it according to the network policy if it fails
+/// because of a server or connection problem
+async fn github_request<D: DeserializeOwned>(
+	client: &Client,
+	url: &str,
+	out: &mut impl OutputTrait,
+) -> anyhow::Result<D> {
+	// This can't use retry because the request needs the output as well
+	let policy = get_network_policy();
+	let mut attempt = 0;
+	loop {
+		match github_request_once(client, url, out).await {
+			Err(e) if policy.should_retry(&e, attempt) => {
+				policy.wait_to_retry(&e, attempt, out).await;
+				attempt += 1;
+			}
+			result => return result,
+		}
+	}
+}
+
+/// Request JSON from the Github API, using the cached response if it has not changed and
+/// waiting or failing with an explanation if the rate limit is reached
+async fn github_request_once<D: DeserializeOwned>(
+	client: &Client,
+	url: &str,
+	out: &mut impl OutputTrait,
+) -> anyhow::Result<D> {
+	let (token, cache_path) = {
+		let settings = GITHUB.read().unwrap_or_else(PoisonError::into_inner);
+		let cache_path = settings
+			.cache_dir
+			.as_ref()
+			.map(|x| x.join(format!("{}.json", get_url_hash(url))));
+		(settings.token.clone(), cache_path)
+	};
+	let cached = cache_path.as_deref().and_then(CachedGithubResponse::load);
+
+	let mut retried = false;
+	let response = loop {
+		let mut req = request(client, url).header(ACCEPT, "application/vnd.github+json");
+		if let Some(token) = &token {
+			req = req.bearer_auth(token);
+		}
+		if let Some(cached) = &cached {
+			req = req.header(IF_NONE_MATCH, &cached.etag);
+		}
+		let response = send(req).await?;
+
+		match get_github_rate_limit(&response, token.is_some()) {
+			None => break response,
+			Some(RateLimit::RetryAfter(wait)) if !retried && wait <= MAX_GITHUB_RETRY_WAIT => {
+				out.progress(format!(
+					"Github asked to wait {} seconds before making more requests",
+					wait.as_secs()
+				));
+				tokio::time::sleep(wait).await;
+				retried = true;
+			}
+			Some(limit) => bail!("{limit}"),
+		}
+	};
+
+	if response.status() == StatusCode::NOT_MODIFIED {
+		if let Some(cached) = cached {
+			let out = serde_json::from_value(cached.body)
+				.context("Failed to parse cached response JSON")?;
+			return Ok(out);
+		}
+	}
+
+	let response = response.error_for_status()?;
+	let etag = get_header(&response, ETAG);
+	let body: serde_json::Value = with_timeout(&get_network_policy(), response.json())
+		.await?
+		.context("Failed to parse response JSON")?;
+	if let (Some(path), Some(etag)) = (cache_path, etag) {
+		// The cache only saves quota, so failing to write it is not an error
+		let _ = CachedGithubResponse {
+			etag,
+			body: body.clone(),
+		}
+		.save(&path);
+	}
+
+	let out = serde_json::from_value(body).context("Failed to parse response JSON")?;
 	Ok(out)
 }
 
+/// A response from the Github API that was cached with its ETag
+#[derive(Serialize, Deserialize)]
+struct CachedGithubResponse {
+	etag: String,
+	body: serde_json::Value,
+}
+
+impl CachedGithubResponse {
+	fn load(path: &Path) -> Option<Self> {
+		let contents = std::fs::read_to_string(path).ok()?;
+		serde_json::from_str(&contents).ok()
+	}
+
+	fn save(&self, path: &Path) -> anyhow::Result<()> {
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		std::fs::write(path, serde_json::to_string(self)?)?;
+		Ok(())
+	}
+}
+
+/// Why the Github API refused a request
+enum RateLimit {
+	/// The hourly limit is used up until the given time
+	Exhausted {
+		reset: Option<DateTime<Local>>,
+		authenticated: bool,
+	},
+	/// Too many requests were made too quickly. Requests can be made again after the duration
+	RetryAfter(Duration),
+}
+
+impl Display for RateLimit {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Exhausted {
+				reset,
+				authenticated,
+			} => {
+				write!(f, "The Github API rate limit was reached")?;
+				if let Some(reset) = reset {
+					write!(f, " and resets at {}", reset.format("%H:%M"))?;
+				}
+				if !*authenticated {
+					write!(
+						f,
+						". Set the github_token config option or the {GITHUB_TOKEN_VAR} environment variable to get a higher limit"
+					)?;
+				}
+				Ok(())
+			}
+			Self::RetryAfter(wait) => write!(
+				f,
+				"The Github API asked to wait {} seconds before making more requests. Try again later",
+				wait.as_secs()
+			),
+		}
+	}
+}
+
+/// Check if a response from the Github API was refused because of a rate limit
+fn get_github_rate_limit(response: &reqwest::Response, authenticated: bool) -> Option<RateLimit> {
+	let status = response.status();
+	if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+		return None;
+	}
+
+	if let Some(wait) = get_header(response, RETRY_AFTER).and_then(|x| x.parse().ok()) {
+		return Some(RateLimit::RetryAfter(Duration::from_secs(wait)));
+	}
+	if get_header(response, HeaderName::from_static("x-ratelimit-remaining")).as_deref()
+		== Some("0")
+	{
+		let reset = get_header(response, HeaderName::from_static("x-ratelimit-reset"))
+			.and_then(|x| x.parse().ok())
+			.and_then(|x| DateTime::from_timestamp(x, 0))
+			.map(|x| x.with_timezone(&Local));
+		return Some(RateLimit::Exhausted {
+			reset,
+			authenticated,
+		});
+	}
+
+	None
+}
+
 /// A single Github release
 #[derive(Deserialize)]
 pub struct GithubRelease {
 	pub tag_name: String,
+	#[serde(default)]
+	pub prerelease: bool,
+	/// Drafts are only listed for users that can edit the repository
+	#[serde(default)]
+	pub draft: bool,
 	pub assets: Vec<GithubReleaseAsset>,
 }
 
@@ -98,14 +574,20 @@ pub struct GithubReleaseAsset {
 	pub url: String,
 	/// The URL to the actual file
 	pub browser_download_url: String,
+	/// Digest of the file, like `sha256:<hex>`. Only set for newer releases
+	#[serde(default)]
+	pub digest: Option<String>,
 }
 
-impl GithubRelease {
-	/// Get the first asset who's name matches a pattern
-	pub fn get_asset_pattern(&self, pat: &str) -> Option<&GithubReleaseAsset> {
-		self.get_asset_patterns(&[pat])
+impl GithubReleaseAsset {
+	/// Get the SHA-256 checksum of the asset in hex, if Github has one
+	pub fn get_sha256(&self) -> Option<String> {
+		let hash = self.digest.as_deref()?.strip_prefix("sha256:")?;
+		Some(hash.to_lowercase())
 	}
+}
 
+impl GithubRelease {
 	/// Get the first asset who's name matches multiple patterns
 	pub fn get_asset_patterns(&self, pats: &[&str]) -> Option<&GithubReleaseAsset> {
 		self.assets
@@ -172,29 +654,186 @@ pub fn get_data_dir(project: &str) -> anyhow::Result<PathBuf> {
 	Ok(out)
 }
 
-/// Gets a local data directory and ensures it exists using ProjectDirs
-pub fn get_local_data_dir(project: &str) -> anyhow::Result<PathBuf> {
-	let out = get_simple_project_dirs(project)?
-		.data_local_dir()
-		.parent()
-		.ok_or(anyhow!("Failed to get parent local data directory"))?
-		.to_owned();
-
-	std::fs::create_dir_all(&out).context("Failed to ensure local data directory exists")?;
-
-	Ok(out)
-}
-
-/// Gets a local program file and ensures the directories leading up to it exist
-pub fn get_local_program(project: &str, exec_name: &str) -> anyhow::Result<PathBuf> {
-	let programs_dir =
-		get_local_data_dir("Programs").context("Failed to get programs directory")?;
-	let dir = programs_dir.join(project);
-	std::fs::create_dir_all(&dir)?;
-	Ok(dir.join(exec_name))
+/// Gets the base user directories
+pub fn get_base_dirs() -> anyhow::Result<BaseDirs> {
+	BaseDirs::new().ok_or(anyhow!("Failed to get base directories"))
 }
 
 fn get_simple_project_dirs(project: &str) -> anyhow::Result<ProjectDirs> {
 	let out = ProjectDirs::from("", "", project).ok_or(anyhow!("Failed to get directories"))?;
 	Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+	use std::sync::{Arc, Mutex};
+
+	use reqwest::header::{HeaderMap, HeaderValue, CONNECTION};
+	use tiny_http::{Header, Response, Server};
+
+	use super::*;
+	use crate::output::NullOutput;
+
+	/// What the test server serves and how it behaves
+	struct ServerState {
+		contents: Vec<u8>,
+		etag: String,
+		/// Whether Range requests are honored
+		ranges: bool,
+		/// Cut the next response off after this many bytes
+		truncate: Option<usize>,
+		/// The Range header of every request
+		requests: Vec<Option<String>>,
+	}
+
+	/// Serve one file on a local port, like a download server would
+	fn serve(contents: &[u8], ranges: bool) -> (String, Arc<Mutex<ServerState>>) {
+		let server = Server::http("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/file.bin", server.server_addr());
+		let state = Arc::new(Mutex::new(ServerState {
+			contents: contents.to_vec(),
+			etag: "\"v1\"".into(),
+			ranges,
+			truncate: None,
+			requests: Vec::new(),
+		}));
+
+		let thread_state = state.clone();
+		std::thread::spawn(move || {
+			for request in server.incoming_requests() {
+				let get = |name: &'static str| {
+					let header = request.headers().iter().find(|x| x.field.equiv(name));
+					header.map(|x| x.value.to_string())
+				};
+				let range = get("Range");
+				let if_range = get("If-Range");
+
+				let mut state = thread_state.lock().unwrap();
+				state.requests.push(range.clone());
+				let start = range
+					.and_then(|x| x.strip_prefix("bytes=")?.strip_suffix('-')?.parse().ok())
+					.filter(|_| state.ranges && if_range.as_ref() == Some(&state.etag));
+
+				let len = state.contents.len();
+				let mut headers = vec![Header::from_bytes("ETag", state.etag.as_str()).unwrap()];
+				let (status, mut body) = match start {
+					Some(start) => {
+						let range = format!("bytes {start}-{}/{len}", len - 1);
+						headers.push(Header::from_bytes("Content-Range", range).unwrap());
+						(206, state.contents[start..].to_vec())
+					}
+					None => (200, state.contents.clone()),
+				};
+				// The advertised length stays the same so that the client sees the connection end early
+				let body_len = body.len();
+				if let Some(truncate) = state.truncate.take() {
+					body.truncate(truncate);
+				}
+				let response = Response::new(
+					status.into(),
+					headers,
+					Cursor::new(body),
+					Some(body_len),
+					None,
+				);
+				drop(state);
+				let _ = request.respond(response);
+			}
+		});
+
+		(url, state)
+	}
+
+	/// A client that closes each connection, so that a cut off response ends instead of hanging
+	fn client() -> Client {
+		let mut headers = HeaderMap::new();
+		headers.insert(CONNECTION, HeaderValue::from_static("close"));
+		Client::builder().default_headers(headers).build().unwrap()
+	}
+
+	/// Test contents that are small enough to be sent with a Content-Length
+	fn contents() -> Vec<u8> {
+		(0..20_000u32).map(|x| (x % 251) as u8).collect()
+	}
+
+	fn sha256(bytes: &[u8]) -> String {
+		format!("{:x}", Sha256::digest(bytes))
+	}
+
+	/// Start a download that is cut off partway and check what was kept
+	async fn interrupt(client: &Client, url: &str, path: &Path, state: &Mutex<ServerState>) {
+		state.lock().unwrap().truncate = Some(5_000);
+		let temp_path = get_partial_path(path, "part");
+		let meta_path = get_partial_path(path, "part.json");
+		let result = download_to(client, url, &temp_path, &meta_path, &mut NullOutput).await;
+
+		assert!(result.is_err());
+		assert_eq!(std::fs::metadata(&temp_path).unwrap().len(), 5_000);
+		assert!(meta_path.exists());
+	}
+
+	#[tokio::test]
+	async fn test_download_resume() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("file.bin");
+		let contents = contents();
+		let (url, state) = serve(&contents, true);
+		let client = client();
+
+		interrupt(&client, &url, &path, &state).await;
+		let sha256 = download_file(&client, &url, &path, &mut NullOutput)
+			.await
+			.unwrap();
+
+		assert_eq!(std::fs::read(&path).unwrap(), contents);
+		assert_eq!(sha256, super::tests::sha256(&contents));
+		assert!(!get_partial_path(&path, "part").exists());
+		assert!(!get_partial_path(&path, "part.json").exists());
+		let requests = state.lock().unwrap().requests.clone();
+		assert_eq!(requests, [None, Some("bytes=5000-".into())]);
+	}
+
+	#[tokio::test]
+	async fn test_download_resume_without_ranges() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("file.bin");
+		let contents = contents();
+		let (url, state) = serve(&contents, false);
+		let client = client();
+
+		interrupt(&client, &url, &path, &state).await;
+		let sha256 = download_file(&client, &url, &path, &mut NullOutput)
+			.await
+			.unwrap();
+
+		// The server sends the whole file again, which replaces the partial download
+		assert_eq!(std::fs::read(&path).unwrap(), contents);
+		assert_eq!(sha256, super::tests::sha256(&contents));
+		let requests = state.lock().unwrap().requests.clone();
+		assert_eq!(requests, [None, Some("bytes=5000-".into())]);
+	}
+
+	#[tokio::test]
+	async fn test_download_resume_changed_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("file.bin");
+		let (url, state) = serve(&contents(), true);
+		let client = client();
+
+		interrupt(&client, &url, &path, &state).await;
+		let changed: Vec<u8> = contents().iter().map(|x| x.wrapping_add(1)).collect();
+		{
+			let mut state = state.lock().unwrap();
+			state.contents = changed.clone();
+			state.etag = "\"v2\"".into();
+		}
+		let sha256 = download_file(&client, &url, &path, &mut NullOutput)
+			.await
+			.unwrap();
+
+		// If-Range does not match the new ETag, so the new file is downloaded from the start
+		assert_eq!(std::fs::read(&path).unwrap(), changed);
+		assert_eq!(sha256, super::tests::sha256(&changed));
+	}
+}
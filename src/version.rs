@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+
+/// Get the numeric components of a version string, ignoring any prefix or separators.
+/// For example, `v2023.4.3` becomes `[2023, 4, 3]`
+pub fn version_components(version: &str) -> Vec<u64> {
+	version
+		.split(|c: char| !c.is_ascii_digit())
+		.filter(|x| !x.is_empty())
+		.filter_map(|x| x.parse().ok())
+		.collect()
+}
+
+/// Split a version string into its release part and its pre-release suffix, ignoring any
+/// prefix and build metadata. For example, `v2024.1.0-beta-2` becomes `("2024.1.0", "beta-2")`
+fn split_prerelease(version: &str) -> (&str, &str) {
+	let version = version.split('+').next().unwrap_or_default();
+	let start = version
+		.find(|c: char| c.is_ascii_digit())
+		.unwrap_or(version.len());
+	let version = &version[start..];
+	let end = version
+		.find(|c: char| !c.is_ascii_digit() && c != '.')
+		.unwrap_or(version.len());
+	let (release, suffix) = version.split_at(end);
+
+	(release, suffix.trim_start_matches(['-', '.', '_', ' ']))
+}
+
+/// A part of a pre-release suffix. Numbers sort before words, like in semver
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+	Number(u64),
+	Word(String),
+}
+
+/// Split a pre-release suffix into runs of digits and letters.
+/// For example, `beta-2` becomes `[Word("beta"), Number(2)]`
+fn prerelease_identifiers(suffix: &str) -> Vec<Identifier> {
+	let mut out = Vec::new();
+	let mut rest = suffix;
+	while let Some(start) = rest.find(|c: char| c.is_ascii_alphanumeric()) {
+		rest = &rest[start..];
+		let is_number = rest.starts_with(|c: char| c.is_ascii_digit());
+		let end = rest
+			.find(|c: char| !c.is_ascii_alphanumeric() || c.is_ascii_digit() != is_number)
+			.unwrap_or(rest.len());
+		let part = &rest[..end];
+		out.push(if is_number {
+			Identifier::Number(part.parse().unwrap_or(u64::MAX))
+		} else {
+			Identifier::Word(part.to_ascii_lowercase())
+		});
+		rest = &rest[end..];
+	}
+
+	out
+}
+
+/// Compare two version strings by their numeric components. Missing components count as zero.
+/// A pre-release such as `2024.1.0-beta-2` is older than the release it comes before
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+	let (a_release, a_prerelease) = split_prerelease(a);
+	let (b_release, b_prerelease) = split_prerelease(b);
+	let a = version_components(a_release);
+	let b = version_components(b_release);
+	let len = a.len().max(b.len());
+	for i in 0..len {
+		let x = a.get(i).copied().unwrap_or(0);
+		let y = b.get(i).copied().unwrap_or(0);
+		match x.cmp(&y) {
+			Ordering::Equal => continue,
+			other => return other,
+		}
+	}
+
+	match (a_prerelease.is_empty(), b_prerelease.is_empty()) {
+		(true, true) => Ordering::Equal,
+		(true, false) => Ordering::Greater,
+		(false, true) => Ordering::Less,
+		(false, false) => {
+			prerelease_identifiers(a_prerelease).cmp(&prerelease_identifiers(b_prerelease))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_release_order() {
+		assert!(compare_versions("v2024.1.1", "2024.1.0").is_gt());
+		assert!(compare_versions("2024.2", "2024.10").is_lt());
+		assert!(compare_versions("2024.1", "v2024.1.0").is_eq());
+		assert!(compare_versions("2025.0.0", "2024.3.2").is_gt());
+	}
+
+	#[test]
+	fn test_prerelease_is_older() {
+		assert!(compare_versions("2024.1.0-beta-2", "2024.1.0").is_lt());
+		assert!(compare_versions("v2024.1.0", "v2024.1.0-rc1").is_gt());
+		assert!(compare_versions("2024.1.1-beta-1", "2024.1.0").is_gt());
+		assert!(compare_versions("2024.1.0+build5", "2024.1.0").is_eq());
+	}
+
+	#[test]
+	fn test_prerelease_order() {
+		assert!(compare_versions("2024.1.0-beta-2", "2024.1.0-beta-10").is_lt());
+		assert!(compare_versions("2024.1.0-alpha-3", "2024.1.0-beta-1").is_lt());
+		assert!(compare_versions("2024.1.0-beta-2", "2024.1.0-rc-1").is_lt());
+		assert!(compare_versions("2024.1.0-beta", "2024.1.0-beta.1").is_lt());
+	}
+
+	#[test]
+	fn test_newest_prerelease_directory() {
+		let newest = ["2024.1.0-beta-2", "2024.1.0", "2023.4.3"]
+			.into_iter()
+			.max_by(|a, b| compare_versions(a, b));
+		assert_eq!(newest, Some("2024.1.0"));
+	}
+}